@@ -0,0 +1,427 @@
+//! Aggregates over history series
+//!
+//! Every aggregate is weighted by time: a state counts for as long as it was held within the
+//! period, including a state set before the period started. Fetch the history with a start time
+//! slightly before the period to make sure the state at its start is known.
+
+use crate::get::HistorySeries;
+use crate::StateEnum;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, TimeDelta, TimeZone, Timelike};
+
+/// Time-weighted aggregates of the numeric states of a period
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericStats {
+    /// `None` when the numeric states were not held for any measurable time
+    pub mean: Option<f64>,
+    pub min: f64,
+    pub max: f64,
+
+    /// Time covered by numeric states, states such as `unavailable` are left out of the aggregates
+    pub duration: TimeDelta,
+}
+
+/// Aggregates of a series over a period
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    /// `None` when no numeric state was held during the period
+    pub numeric: Option<NumericStats>,
+
+    /// Time spent in each state, keyed by the state formatted as a string
+    pub time_in_state: HashMap<String, TimeDelta>,
+
+    /// Number of state changes during the period
+    pub transitions: usize,
+}
+
+impl Summary {
+    /// Returns the time spent in `state`, zero if the state was never held
+    pub fn time_in(&self, state: &str) -> TimeDelta {
+        self.time_in_state
+            .get(state)
+            .copied()
+            .unwrap_or_else(TimeDelta::zero)
+    }
+}
+
+/// Length of the buckets of [`summarize_buckets`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketSize {
+    Hour,
+    Day,
+}
+
+/// Aggregates of a series over one hour or day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub summary: Summary,
+}
+
+/// Aggregates `series` over `start..end`
+pub fn summarize(
+    series: &HistorySeries,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> Summary {
+    let points = points_in(series, start, end);
+
+    let mut summary = Summary {
+        transitions: points
+            .windows(2)
+            .filter(|pair| pair[0].1 != pair[1].1)
+            .count(),
+        ..Summary::default()
+    };
+
+    let mut weighted_sum = 0.0;
+    let mut numeric: Option<NumericStats> = None;
+
+    for (duration, state) in clipped_intervals(points, start, end) {
+        *summary
+            .time_in_state
+            .entry(state.to_string())
+            .or_insert_with(TimeDelta::zero) += duration;
+
+        let Some(value) = state.as_f64() else {
+            continue;
+        };
+
+        weighted_sum += value * seconds(duration);
+
+        match numeric.as_mut() {
+            Some(stats) => {
+                stats.min = stats.min.min(value);
+                stats.max = stats.max.max(value);
+                stats.duration += duration;
+            }
+            None => {
+                numeric = Some(NumericStats {
+                    mean: None,
+                    min: value,
+                    max: value,
+                    duration,
+                })
+            }
+        }
+    }
+
+    summary.numeric = numeric.map(|stats| {
+        let weight = seconds(stats.duration);
+
+        NumericStats {
+            mean: (weight > 0.0).then(|| weighted_sum / weight),
+            ..stats
+        }
+    });
+
+    summary
+}
+
+/// Aggregates `series` over every hour or day of `start..end` in the time zone `tz`
+///
+/// Buckets follow the wall clock of `tz`, so days are 23 or 25 hours long when daylight saving
+/// time starts or ends. The first and last buckets are truncated to `start` and `end`.
+pub fn summarize_buckets<Tz: TimeZone>(
+    series: &HistorySeries,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    size: BucketSize,
+    tz: &Tz,
+) -> Vec<Bucket> {
+    let mut buckets = Vec::new();
+    let mut bucket_start = floor(start, size, tz);
+
+    while bucket_start < end {
+        let mut next = match size {
+            BucketSize::Hour => floor(bucket_start + TimeDelta::hours(1), size, tz),
+            BucketSize::Day => bucket_start
+                .with_timezone(tz)
+                .date_naive()
+                .succ_opt()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map_or(end, |midnight| local_instant(tz, midnight, None)),
+        };
+
+        if next <= bucket_start {
+            next = bucket_start + TimeDelta::hours(1);
+        }
+
+        let (clipped_start, clipped_end) = (bucket_start.max(start), next.min(end));
+        buckets.push(Bucket {
+            start: clipped_start,
+            end: clipped_end,
+            summary: summarize(series, clipped_start, clipped_end),
+        });
+
+        bucket_start = next;
+    }
+
+    buckets
+}
+
+/// Returns the length of `duration` in seconds, keeping its full nanosecond precision
+fn seconds(duration: TimeDelta) -> f64 {
+    duration.num_seconds() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+/// Returns the points relevant to `start..end`: the one in effect at `start` and the following
+/// ones before `end`
+fn points_in(
+    series: &HistorySeries,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> &[(DateTime<FixedOffset>, StateEnum)] {
+    let first = series
+        .points
+        .partition_point(|(time, _)| *time <= start)
+        .saturating_sub(1);
+    let last = series.points.partition_point(|(time, _)| *time < end);
+
+    &series.points[first..last.max(first)]
+}
+
+fn clipped_intervals(
+    points: &[(DateTime<FixedOffset>, StateEnum)],
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> impl Iterator<Item = (TimeDelta, &StateEnum)> {
+    points
+        .iter()
+        .enumerate()
+        .filter_map(move |(index, (time, state))| {
+            let interval_start = (*time).max(start);
+            let interval_end = points
+                .get(index + 1)
+                .map_or(end, |(next, _)| *next)
+                .min(end);
+
+            (interval_end > interval_start).then(|| (interval_end - interval_start, state))
+        })
+}
+
+/// Returns the start of the hour or day containing `time` in the time zone `tz`
+fn floor<Tz: TimeZone>(
+    time: DateTime<FixedOffset>,
+    size: BucketSize,
+    tz: &Tz,
+) -> DateTime<FixedOffset> {
+    let local = time.with_timezone(tz).naive_local();
+    let hour = match size {
+        BucketSize::Hour => local.hour(),
+        BucketSize::Day => 0,
+    };
+
+    local
+        .date()
+        .and_hms_opt(hour, 0, 0)
+        .map_or(time, |floored| local_instant(tz, floored, Some(time)))
+}
+
+/// Converts a wall clock time of `tz` to an instant
+///
+/// When the wall clock time happens twice, the latest instant not after `not_after` is used. Wall
+/// clock times skipped by daylight saving time resolve to the end of the gap.
+fn local_instant<Tz: TimeZone>(
+    tz: &Tz,
+    local: NaiveDateTime,
+    not_after: Option<DateTime<FixedOffset>>,
+) -> DateTime<FixedOffset> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(instant) => instant.fixed_offset(),
+        LocalResult::Ambiguous(earliest, latest) => {
+            let latest = latest.fixed_offset();
+
+            if not_after.is_some_and(|not_after| latest <= not_after) {
+                latest
+            } else {
+                earliest.fixed_offset()
+            }
+        }
+        LocalResult::None => local_instant(tz, local + TimeDelta::hours(1), not_after),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn time(day: u32, hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!(
+            "2024-03-{:02}T{:02}:{:02}:00+00:00",
+            day, hour, minute
+        ))
+        .unwrap()
+    }
+
+    fn series(points: Vec<(DateTime<FixedOffset>, StateEnum)>) -> HistorySeries {
+        HistorySeries {
+            entity_id: "sensor.test".to_owned(),
+            points,
+        }
+    }
+
+    #[test]
+    fn test_numeric_summary() {
+        let temperature = series(vec![
+            (time(1, 0, 0), StateEnum::Integer(10)),
+            (time(1, 3, 0), StateEnum::Decimal(20.0)),
+            (time(1, 4, 0), StateEnum::String("unavailable".to_owned())),
+            (time(1, 5, 0), StateEnum::Integer(16)),
+        ]);
+
+        let summary = summarize(&temperature, time(1, 1, 0), time(1, 6, 0));
+        let numeric = summary.numeric.as_ref().unwrap();
+
+        // 2h at 10, 1h at 20, 1h at 16
+        assert_eq!(numeric.mean, Some(14.0));
+        assert_eq!(numeric.min, 10.0);
+        assert_eq!(numeric.max, 20.0);
+        assert_eq!(numeric.duration, TimeDelta::hours(4));
+        assert_eq!(summary.time_in("unavailable"), TimeDelta::hours(1));
+        assert_eq!(summary.transitions, 3);
+    }
+
+    #[test]
+    fn test_time_in_state() {
+        let door = series(vec![
+            (time(1, 0, 0), StateEnum::String("off".to_owned())),
+            (time(1, 8, 0), StateEnum::String("on".to_owned())),
+            (time(1, 8, 15), StateEnum::String("off".to_owned())),
+            (time(1, 8, 30), StateEnum::String("off".to_owned())),
+            (time(1, 18, 0), StateEnum::String("on".to_owned())),
+        ]);
+
+        let summary = summarize(&door, time(1, 6, 0), time(1, 19, 0));
+
+        assert_eq!(summary.numeric, None);
+        assert_eq!(summary.time_in("on"), TimeDelta::minutes(75));
+        assert_eq!(summary.time_in("off"), TimeDelta::minutes(705));
+        assert_eq!(summary.time_in("unknown"), TimeDelta::zero());
+        assert_eq!(summary.transitions, 3);
+    }
+
+    #[test]
+    fn test_sub_millisecond_period() {
+        let power = series(vec![
+            (time(1, 0, 0), StateEnum::Integer(100)),
+            (time(1, 1, 0), StateEnum::Integer(300)),
+        ]);
+        let start = time(1, 1, 0) - TimeDelta::microseconds(300);
+        let end = time(1, 1, 0) + TimeDelta::microseconds(100);
+
+        let numeric = summarize(&power, start, end).numeric.unwrap();
+
+        assert_eq!(numeric.mean, Some(150.0));
+        assert_eq!(numeric.duration, TimeDelta::microseconds(400));
+    }
+
+    #[test]
+    fn test_empty_period() {
+        let summary = summarize(
+            &series(vec![(time(2, 0, 0), StateEnum::Integer(1))]),
+            time(1, 0, 0),
+            time(1, 12, 0),
+        );
+
+        assert_eq!(summary, Summary::default());
+    }
+
+    #[test]
+    fn test_hour_buckets() {
+        let power = series(vec![
+            (time(1, 0, 0), StateEnum::Integer(100)),
+            (time(1, 1, 30), StateEnum::Integer(300)),
+        ]);
+        let tz = FixedOffset::east_opt(30 * 60).unwrap();
+
+        let buckets =
+            summarize_buckets(&power, time(1, 0, 0), time(1, 2, 30), BucketSize::Hour, &tz);
+
+        assert_eq!(
+            buckets
+                .iter()
+                .map(|bucket| (bucket.start, bucket.end))
+                .collect::<Vec<_>>(),
+            vec![
+                (time(1, 0, 0), time(1, 0, 30)),
+                (time(1, 0, 30), time(1, 1, 30)),
+                (time(1, 1, 30), time(1, 2, 30)),
+            ]
+        );
+        assert_eq!(
+            buckets[1].summary.numeric.as_ref().unwrap().mean,
+            Some(100.0)
+        );
+        assert_eq!(
+            buckets[2].summary.numeric.as_ref().unwrap().mean,
+            Some(300.0)
+        );
+    }
+
+    #[test]
+    fn test_day_buckets() {
+        let light = series(vec![
+            (time(1, 0, 0), StateEnum::String("off".to_owned())),
+            (time(1, 22, 0), StateEnum::String("on".to_owned())),
+            (time(2, 1, 0), StateEnum::String("off".to_owned())),
+        ]);
+        let tz = FixedOffset::west_opt(2 * 3600).unwrap();
+
+        let buckets = summarize_buckets(&light, time(1, 0, 0), time(3, 0, 0), BucketSize::Day, &tz);
+
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].end, time(1, 2, 0));
+        assert_eq!(buckets[1].start, time(1, 2, 0));
+        assert_eq!(buckets[1].end, time(2, 2, 0));
+        assert_eq!(buckets[1].summary.time_in("on"), TimeDelta::hours(3));
+        assert_eq!(buckets[1].summary.transitions, 2);
+        assert_eq!(buckets[2].summary.time_in("off"), TimeDelta::hours(22));
+    }
+
+    #[test]
+    fn test_buckets_across_daylight_saving_time() {
+        let instant = |time: &str| DateTime::parse_from_rfc3339(time).unwrap();
+        let flat = series(vec![(
+            instant("2024-01-01T00:00:00Z"),
+            StateEnum::Integer(1),
+        )]);
+        let durations = |buckets: Vec<Bucket>| {
+            buckets
+                .iter()
+                .map(|bucket| (bucket.end - bucket.start).num_hours())
+                .collect::<Vec<_>>()
+        };
+
+        let spring = summarize_buckets(
+            &flat,
+            instant("2024-03-30T23:00:00Z"),
+            instant("2024-04-01T22:00:00Z"),
+            BucketSize::Day,
+            &chrono_tz::Europe::Berlin,
+        );
+        assert_eq!(spring[1].start, instant("2024-03-31T22:00:00Z"));
+        assert_eq!(durations(spring), vec![23, 24]);
+
+        let autumn = summarize_buckets(
+            &flat,
+            instant("2024-10-26T22:00:00Z"),
+            instant("2024-10-27T23:00:00Z"),
+            BucketSize::Day,
+            &chrono_tz::Europe::Berlin,
+        );
+        assert_eq!(durations(autumn), vec![25]);
+
+        let repeated_hour = summarize_buckets(
+            &flat,
+            instant("2024-10-27T00:00:00Z"),
+            instant("2024-10-27T02:00:00Z"),
+            BucketSize::Hour,
+            &chrono_tz::Europe::Berlin,
+        );
+        assert_eq!(durations(repeated_hour), vec![1, 1]);
+    }
+}
@@ -0,0 +1,453 @@
+//! MJPEG camera streams served by `/api/camera_proxy_stream/<entity_id>`
+
+use crate::{errors, get, image};
+
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures_util::Stream;
+
+type Result<T> = std::result::Result<T, errors::Error>;
+
+/// Largest part accepted from a camera stream, far above the size of a camera frame
+const MAX_PART_SIZE: usize = 16 * 1024 * 1024;
+
+/// Async [`Stream`] of the frames of an MJPEG camera stream
+///
+/// Created by [`Client::get_camera_stream`](crate::Client::get_camera_stream). The stream parses
+/// the `multipart/x-mixed-replace` response as it arrives and yields one [`get::CameraFrame`] per
+/// part. It ends when Home Assistant closes the connection or ends the multipart body. A part
+/// larger than 16 MiB is reported as [`errors::Error::UnexpectedResponse`] and ends the stream.
+/// Dropping the stream closes the underlying connection, so cancelling a consumer is as simple as
+/// dropping it.
+pub struct CameraStream {
+    body: Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>,
+    parser: MultipartParser,
+    min_frame_interval: Option<Duration>,
+    last_frame: Option<Instant>,
+}
+
+impl CameraStream {
+    pub(crate) fn new(
+        response: reqwest::Response,
+        min_frame_interval: Option<Duration>,
+    ) -> Result<Self> {
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        let boundary = boundary(content_type).ok_or_else(|| {
+            errors::Error::UnexpectedResponse(format!(
+                "expected a multipart/x-mixed-replace response but the content type was \"{}\"",
+                content_type
+            ))
+        })?;
+
+        Ok(CameraStream {
+            parser: MultipartParser::new(boundary, MAX_PART_SIZE),
+            body: Box::pin(response.bytes_stream()),
+            min_frame_interval,
+            last_frame: None,
+        })
+    }
+
+    /// Returns `true` if the frame should be dropped to honor the minimum frame interval
+    fn throttled(&mut self) -> bool {
+        let now = Instant::now();
+
+        if let (Some(interval), Some(last_frame)) = (self.min_frame_interval, self.last_frame) {
+            if now.duration_since(last_frame) < interval {
+                return true;
+            }
+        }
+
+        self.last_frame = Some(now);
+        false
+    }
+}
+
+impl fmt::Debug for CameraStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CameraStream")
+            .field("boundary", &String::from_utf8_lossy(&self.parser.delimiter))
+            .field("min_frame_interval", &self.min_frame_interval)
+            .finish()
+    }
+}
+
+impl Stream for CameraStream {
+    type Item = Result<get::CameraFrame>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.parser.next_part() {
+                Ok(Some(_)) if self.throttled() => continue,
+                Ok(Some(part)) => {
+                    return Poll::Ready(Some(Ok(get::CameraFrame {
+                        timestamp: chrono::Utc::now().fixed_offset(),
+                        dimensions: image::dimensions(&part.body),
+                        content_type: part.content_type,
+                        image: part.body,
+                    })))
+                }
+                Ok(None) if self.parser.is_done() => return Poll::Ready(None),
+                Ok(None) => {}
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+
+            match self.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => self.parser.push(&chunk),
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Extracts the boundary from a `multipart/x-mixed-replace` content type
+fn boundary(content_type: &str) -> Option<String> {
+    let mut parameters = content_type.split(';');
+
+    if !parameters
+        .next()?
+        .trim()
+        .eq_ignore_ascii_case("multipart/x-mixed-replace")
+    {
+        return None;
+    }
+
+    parameters.find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"').to_owned())
+    })
+}
+
+struct Part {
+    content_type: Option<String>,
+    body: Bytes,
+}
+
+enum ParserState {
+    Preamble,
+    Headers,
+    Body {
+        content_type: Option<String>,
+        content_length: Option<usize>,
+    },
+    Done,
+}
+
+/// Incremental parser for `multipart/x-mixed-replace` bodies
+struct MultipartParser {
+    delimiter: Vec<u8>,
+
+    /// Boundary used as is, when it starts with `--` and it is not known yet whether the parts
+    /// are delimited with it or with the RFC 2046 delimiter
+    bare_delimiter: Option<Vec<u8>>,
+
+    buffer: BytesMut,
+
+    /// Number of bytes of the buffer already searched without a match in the current state
+    scanned: usize,
+
+    max_part_size: usize,
+    state: ParserState,
+}
+
+impl MultipartParser {
+    fn new(boundary: String, max_part_size: usize) -> Self {
+        // Home Assistant declares its boundary as `--frameboundary` and delimits parts with that
+        // exact string rather than prefixing it with another `--` as RFC 2046 specifies. Other
+        // cameras proxied by Home Assistant may use a boundary starting with `--` as specified.
+        let bare_delimiter = boundary
+            .starts_with("--")
+            .then(|| boundary.clone().into_bytes());
+
+        MultipartParser {
+            delimiter: format!("--{}", boundary).into_bytes(),
+            bare_delimiter,
+            buffer: BytesMut::new(),
+            scanned: 0,
+            max_part_size,
+            state: ParserState::Preamble,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        matches!(self.state, ParserState::Done)
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        if !self.is_done() {
+            self.buffer.extend_from_slice(chunk);
+        }
+    }
+
+    /// Returns the next complete part, or `None` until more of the body is pushed
+    ///
+    /// Fails once the part being received grows beyond the maximum part size, after which the
+    /// parser is done.
+    fn next_part(&mut self) -> Result<Option<Part>> {
+        if let Some(part) = self.parse_part() {
+            return Ok(Some(part));
+        }
+
+        let pending = match self.state {
+            ParserState::Body {
+                content_length: Some(length),
+                ..
+            } => length,
+            _ => self.buffer.len(),
+        };
+
+        if pending > self.max_part_size {
+            self.state = ParserState::Done;
+            self.buffer.clear();
+            return Err(errors::Error::UnexpectedResponse(format!(
+                "a part of the camera stream exceeds {} bytes",
+                self.max_part_size
+            )));
+        }
+
+        Ok(None)
+    }
+
+    fn parse_part(&mut self) -> Option<Part> {
+        loop {
+            match self.state {
+                ParserState::Preamble => {
+                    if let Some(bare_delimiter) = self.bare_delimiter.as_ref() {
+                        let Some(start) = find(&self.buffer, bare_delimiter) else {
+                            let keep = self.delimiter.len().min(self.buffer.len());
+                            self.buffer.advance(self.buffer.len() - keep);
+                            return None;
+                        };
+
+                        // The RFC 2046 delimiter is the bare boundary preceded by `--`
+                        if start < 2 || &self.buffer[start - 2..start] != b"--" {
+                            self.delimiter.clone_from(bare_delimiter);
+                        }
+                        self.bare_delimiter = None;
+                    }
+
+                    let Some(start) = find(&self.buffer, &self.delimiter) else {
+                        // Keep enough bytes to match a delimiter split across chunks
+                        let keep = self.delimiter.len().min(self.buffer.len());
+                        self.buffer.advance(self.buffer.len() - keep);
+                        return None;
+                    };
+
+                    let after_delimiter = start + self.delimiter.len();
+                    if self.buffer.get(after_delimiter..after_delimiter + 2)? == b"--" {
+                        self.state = ParserState::Done;
+                        continue;
+                    }
+
+                    let line_end = find(&self.buffer[after_delimiter..], b"\r\n")?;
+                    self.buffer.advance(after_delimiter + line_end + 2);
+                    self.scanned = 0;
+                    self.state = ParserState::Headers;
+                }
+                ParserState::Headers => {
+                    let (headers, consumed) = if self.buffer.starts_with(b"\r\n") {
+                        (&self.buffer[..0], 2)
+                    } else {
+                        let end = find_from(&self.buffer, b"\r\n\r\n", &mut self.scanned)?;
+                        (&self.buffer[..end], end + 4)
+                    };
+
+                    let mut content_type = None;
+                    let mut content_length = None;
+
+                    for line in String::from_utf8_lossy(headers).split("\r\n") {
+                        if let Some((name, value)) = line.split_once(':') {
+                            let name = name.trim();
+                            if name.eq_ignore_ascii_case("content-type") {
+                                content_type = Some(value.trim().to_owned());
+                            } else if name.eq_ignore_ascii_case("content-length") {
+                                content_length = value.trim().parse().ok();
+                            }
+                        }
+                    }
+
+                    self.buffer.advance(consumed);
+                    self.scanned = 0;
+                    self.state = ParserState::Body {
+                        content_type,
+                        content_length,
+                    };
+                }
+                ParserState::Body {
+                    ref mut content_type,
+                    content_length,
+                } => {
+                    let length = match content_length {
+                        Some(length) if self.buffer.len() >= length => length,
+                        Some(_) => return None,
+                        None => {
+                            let mut terminator = b"\r\n".to_vec();
+                            terminator.extend_from_slice(&self.delimiter);
+                            find_from(&self.buffer, &terminator, &mut self.scanned)?
+                        }
+                    };
+
+                    let part = Part {
+                        content_type: content_type.take(),
+                        body: self.buffer.split_to(length).freeze(),
+                    };
+
+                    self.scanned = 0;
+                    self.state = ParserState::Preamble;
+                    return Some(part);
+                }
+                ParserState::Done => {
+                    self.buffer.clear();
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Same as [`find`] but resuming after the `scanned` bytes searched by a previous call
+///
+/// When there is no match, `scanned` is moved to the last position a match could start at once
+/// more bytes are appended, so a growing haystack is only searched once.
+fn find_from(haystack: &[u8], needle: &[u8], scanned: &mut usize) -> Option<usize> {
+    let position = find(&haystack[*scanned..], needle).map(|position| *scanned + position);
+    if position.is_none() {
+        *scanned = haystack
+            .len()
+            .saturating_sub(needle.len() - 1)
+            .max(*scanned);
+    }
+
+    position
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_all(parser: &mut MultipartParser) -> Vec<Part> {
+        std::iter::from_fn(|| parser.next_part().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_boundary() {
+        assert_eq!(
+            boundary("multipart/x-mixed-replace;boundary=--frameboundary").as_deref(),
+            Some("--frameboundary")
+        );
+        assert_eq!(
+            boundary("multipart/x-mixed-replace; boundary=\"frame\"").as_deref(),
+            Some("frame")
+        );
+        assert_eq!(boundary("image/jpeg"), None);
+    }
+
+    #[test]
+    fn test_parts_with_content_length() {
+        let mut parser = MultipartParser::new("--frameboundary".to_owned(), MAX_PART_SIZE);
+        parser.push(
+            b"--frameboundary\r\nContent-Type: image/jpeg\r\nContent-Length: 4\r\n\r\nabcd\r\n\
+              --frameboundary\r\nContent-Type: image/jpeg\r\nContent-Length: 2\r\n\r\nef\r\n",
+        );
+
+        let parts = parse_all(&mut parser);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].content_type.as_deref(), Some("image/jpeg"));
+        assert_eq!(parts[0].body.as_ref(), b"abcd");
+        assert_eq!(parts[1].body.as_ref(), b"ef");
+    }
+
+    #[test]
+    fn test_boundary_starting_with_dashes() {
+        let mut parser = MultipartParser::new("--frame".to_owned(), MAX_PART_SIZE);
+        parser.push(
+            b"----frame\r\nContent-Type: image/jpeg\r\n\r\n--frame\r\n----frame\r\n\r\nef\r\n----frame--\r\n",
+        );
+
+        let parts = parse_all(&mut parser);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].body.as_ref(), b"--frame");
+        assert_eq!(parts[1].body.as_ref(), b"ef");
+    }
+
+    #[test]
+    fn test_parts_without_content_length() {
+        let mut parser = MultipartParser::new("frame".to_owned(), MAX_PART_SIZE);
+        parser.push(b"--frame\r\nContent-Type: image/jpeg\r\n\r\nabcd\r\n--frame\r\n\r\nef\r\n--frame--\r\n");
+
+        let parts = parse_all(&mut parser);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].body.as_ref(), b"abcd");
+        assert_eq!(parts[1].content_type, None);
+        assert_eq!(parts[1].body.as_ref(), b"ef");
+    }
+
+    #[test]
+    fn test_parts_split_across_chunks() {
+        let data: &[u8] =
+            b"--frame\r\nContent-Length: 6\r\n\r\n--fram\r\n--frame\r\nContent-Length: 3\r\n\r\nxyz\r\n";
+
+        let mut parser = MultipartParser::new("frame".to_owned(), MAX_PART_SIZE);
+        let mut parts = Vec::new();
+        for byte in data.chunks(1) {
+            parser.push(byte);
+            parts.extend(parse_all(&mut parser));
+        }
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].body.as_ref(), b"--fram");
+        assert_eq!(parts[1].body.as_ref(), b"xyz");
+    }
+
+    #[test]
+    fn test_find_from_resumes_scan() {
+        let mut scanned = 0;
+        assert_eq!(
+            find_from(b"abcdef\r\n--fr", b"\r\n--frame", &mut scanned),
+            None
+        );
+        assert_eq!(scanned, 4);
+        assert_eq!(
+            find_from(b"abcdef\r\n--frame", b"\r\n--frame", &mut scanned),
+            Some(6)
+        );
+    }
+
+    #[test]
+    fn test_part_too_large() {
+        let mut parser = MultipartParser::new("frame".to_owned(), 8);
+        parser.push(b"--frame\r\n\r\nabcd");
+        assert!(parser.next_part().unwrap().is_none());
+
+        parser.push(b"efghi");
+        assert!(matches!(
+            parser.next_part(),
+            Err(errors::Error::UnexpectedResponse(_))
+        ));
+        assert!(parser.is_done());
+
+        let mut parser = MultipartParser::new("frame".to_owned(), 8);
+        parser.push(b"--frame\r\nContent-Length: 9\r\n\r\n");
+        assert!(parser.next_part().is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Splitting of long history and logbook periods into several requests
+
+use crate::get::{History, HistorySeriesResponse, LogbookEntry, LogbookResponse};
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+/// Splits `start..end` into consecutive windows of at most `window`
+///
+/// A single window covering the whole period is returned when `window` is not positive.
+pub(crate) fn windows(
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    window: TimeDelta,
+) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    if window <= TimeDelta::zero() || start >= end {
+        return vec![(start, end)];
+    }
+
+    let mut windows = Vec::new();
+    let mut window_start = start;
+
+    while window_start < end {
+        let window_end = window_start
+            .checked_add_signed(window)
+            .map_or(end, |window_end| window_end.min(end));
+
+        windows.push((window_start, window_end));
+        window_start = window_end;
+    }
+
+    windows
+}
+
+/// Joins the series of consecutive windows into a single series per entity
+pub(crate) fn stitch_history(chunks: Vec<HistorySeriesResponse>) -> History {
+    let mut history = History::default();
+
+    for series in chunks.into_iter().flatten() {
+        history.append_window(series);
+    }
+
+    history
+}
+
+/// Joins the entries of consecutive windows, dropping entries returned by both windows around
+/// a boundary
+pub(crate) fn stitch_logbook(chunks: Vec<LogbookResponse>) -> LogbookResponse {
+    let mut entries: LogbookResponse = Vec::new();
+
+    for chunk in chunks {
+        let last_when = entries.iter().filter_map(|entry| entry.when).max();
+        let known = entries.len();
+
+        for entry in chunk {
+            let duplicate = match (entry.when, last_when) {
+                (Some(when), Some(last_when)) if when < last_when => true,
+                (Some(when), Some(last_when)) if when == last_when => entries[..known]
+                    .iter()
+                    .rev()
+                    .filter(|known| known.when == Some(when))
+                    .any(|known| same_entry(known, &entry)),
+                _ => false,
+            };
+
+            if !duplicate {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries
+}
+
+fn same_entry(a: &LogbookEntry, b: &LogbookEntry) -> bool {
+    a.when == b.when
+        && a.entity_id == b.entity_id
+        && a.message == b.message
+        && a.context_id == b.context_id
+        && a.state == b.state
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{get::HistorySeries, StateEnum};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn time(day: u32, hour: u32) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!("2024-05-{:02}T{:02}:00:00+00:00", day, hour))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_windows() {
+        assert_eq!(
+            windows(time(1, 0), time(3, 12), TimeDelta::days(1)),
+            vec![
+                (time(1, 0), time(2, 0)),
+                (time(2, 0), time(3, 0)),
+                (time(3, 0), time(3, 12)),
+            ]
+        );
+        assert_eq!(
+            windows(time(1, 0), time(3, 12), TimeDelta::zero()),
+            vec![(time(1, 0), time(3, 12))]
+        );
+    }
+
+    #[test]
+    fn test_stitch_history() {
+        let on = || StateEnum::String("on".to_owned());
+        let off = || StateEnum::String("off".to_owned());
+
+        let history = stitch_history(vec![
+            vec![HistorySeries {
+                entity_id: "light.kitchen".to_owned(),
+                points: vec![(time(1, 0), off()), (time(1, 18), on())],
+            }],
+            vec![HistorySeries {
+                entity_id: "light.kitchen".to_owned(),
+                points: vec![(time(2, 0), on()), (time(2, 1), off())],
+            }],
+            vec![HistorySeries {
+                entity_id: "light.kitchen".to_owned(),
+                points: vec![(time(2, 1), off()), (time(3, 0), off())],
+            }],
+        ]);
+
+        assert_eq!(
+            history.get("light.kitchen").unwrap().points,
+            vec![
+                (time(1, 0), off()),
+                (time(1, 18), on()),
+                (time(2, 1), off())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stitch_logbook() {
+        let chunks: Vec<LogbookResponse> = serde_json::from_value(json!([
+            [
+                {"entity_id": "light.kitchen", "state": "on", "when": "2024-05-01T18:00:00+00:00"},
+                {"entity_id": "light.kitchen", "state": "off", "when": "2024-05-02T00:00:00+00:00"}
+            ],
+            [
+                {"entity_id": "light.kitchen", "state": "off", "when": "2024-05-02T00:00:00+00:00"},
+                {"entity_id": "switch.fan", "state": "on", "when": "2024-05-02T00:00:00+00:00"},
+                {"entity_id": "light.kitchen", "state": "on", "when": "2024-05-02T07:00:00+00:00"}
+            ]
+        ]))
+        .unwrap();
+
+        let entries = stitch_logbook(chunks);
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.entity_id.as_deref().unwrap(), entry.when.unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("light.kitchen", time(1, 18)),
+                ("light.kitchen", time(2, 0)),
+                ("switch.fan", time(2, 0)),
+                ("light.kitchen", time(2, 7)),
+            ]
+        );
+    }
+}
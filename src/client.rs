@@ -1,22 +1,190 @@
 //! Home Assistant REST Client
 
 use crate::{
-    errors, get,
+    chunked, entity_state, errors,
+    get::{self, Parameters},
+    image,
     post::{self, Requestable},
+    raw, CameraStream, EntityState,
 };
 
-use std::fmt::Display;
+use std::{
+    fmt,
+    fmt::Display,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use bytes::Bytes;
-use reqwest::RequestBuilder;
+use chrono::TimeDelta;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use reqwest::{Method, RequestBuilder};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use url::Url;
 
+/// Compression applied to the bodies of outgoing POST requests
+///
+/// Bodies are compressed with gzip and sent with a `Content-Encoding: gzip` header. Bodies smaller
+/// than `min_size` bytes are sent uncompressed since the gzip framing would outweigh the savings.
+#[cfg(feature = "gzip")]
+#[derive(Debug, Clone)]
+pub struct RequestCompression {
+    /// Minimum size, in bytes, of the serialized body before it is compressed
+    pub min_size: usize,
+
+    /// Compression level between 0 (none) and 9 (best)
+    pub level: u32,
+}
+
+#[cfg(feature = "gzip")]
+impl Default for RequestCompression {
+    fn default() -> Self {
+        RequestCompression {
+            min_size: 1024,
+            level: 6,
+        }
+    }
+}
+
+/// Connect timeout of clients with several base URLs when none is configured, so that a URL
+/// which drops connections does not hold up failing over for the OS connect timeout
+const FAILOVER_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options used to configure a [`Client`]
+///
+/// Responses compressed with gzip, brotli or zstd are advertised and decoded transparently
+/// depending on which of the `gzip`, `brotli` and `zstd` features are enabled.
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// Compress POST bodies before sending them. Disabled when `None`.
+    #[cfg(feature = "gzip")]
+    pub request_compression: Option<RequestCompression>,
+
+    /// Maximum time to wait while establishing a connection. When the client is configured with
+    /// several base URLs, this bounds how long an unreachable URL delays failing over and defaults
+    /// to 5 seconds when `None`. With a single base URL, `None` leaves the timeout to the OS.
+    pub connect_timeout: Option<Duration>,
+
+    /// Maximum time to wait for a complete response. Only GET requests fail over to the next base
+    /// URL after timing out, other requests may already have been handled by the server.
+    pub timeout: Option<Duration>,
+
+    /// Time to wait after failing over to a less preferred base URL before trying the preferred
+    /// URLs again
+    ///
+    /// Every failback attempt waits for the preferred URLs to connect, which is bounded by
+    /// [`connect_timeout`](ClientOptions::connect_timeout). A preferred URL that silently drops
+    /// connections, like a private `internal_url` reached from outside the LAN, therefore delays
+    /// one request per interval by up to that timeout.
+    pub failback_interval: Duration,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            #[cfg(feature = "gzip")]
+            request_compression: None,
+            connect_timeout: None,
+            timeout: None,
+            failback_interval: Duration::from_secs(60),
+        }
+    }
+}
+
 /// Represents a connection to a Home Assistant instance
+///
+/// `Client` is cheap to clone: clones share the same connection pool, credentials and options
+/// through an [`Arc`], so there is no need to wrap it in one. It is also `Send` and `Sync`, which
+/// allows a single instance to be shared across tasks and threads. The [`Debug`](fmt::Debug)
+/// implementation redacts the access token so that structs holding a `Client` can be logged.
+#[derive(Clone)]
 pub struct Client {
-    url: Url,
+    inner: Arc<ClientInner>,
+}
+
+struct ClientInner {
+    endpoints: Endpoints,
     token: String,
+    http: reqwest::Client,
+    options: ClientOptions,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field(
+                "urls",
+                &self.urls().iter().map(Url::as_str).collect::<Vec<_>>(),
+            )
+            .field("active_url", &self.active_url().as_str())
+            .field("token", &"<redacted>")
+            .field("options", &self.inner.options)
+            .finish()
+    }
+}
+
+/// The ordered base URLs of an instance along with the one currently in use
+struct Endpoints {
+    urls: Vec<Url>,
+    active: Mutex<ActiveEndpoint>,
+    failback_interval: Duration,
+}
+
+struct ActiveEndpoint {
+    index: usize,
+    since: Instant,
+}
+
+impl Endpoints {
+    fn new(urls: Vec<Url>, failback_interval: Duration) -> Self {
+        Endpoints {
+            urls,
+            active: Mutex::new(ActiveEndpoint {
+                index: 0,
+                since: Instant::now(),
+            }),
+            failback_interval,
+        }
+    }
+
+    fn active(&self) -> usize {
+        self.active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .index
+    }
+
+    /// Returns the order in which the URLs should be attempted and whether that order is an
+    /// attempt to fail back to a preferred URL
+    fn attempt_order(&self) -> (Vec<usize>, bool) {
+        let active = self.active.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if active.index > 0 && active.since.elapsed() >= self.failback_interval {
+            return ((0..self.urls.len()).collect(), true);
+        }
+
+        let order = std::iter::once(active.index)
+            .chain((0..self.urls.len()).filter(|index| *index != active.index))
+            .collect();
+
+        (order, false)
+    }
+
+    fn record_success(&self, index: usize, failing_back: bool) {
+        let mut active = self.active.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if active.index != index {
+            log::info!("Switching active URL to {}", self.urls[index]);
+        } else if !failing_back {
+            return;
+        }
+
+        *active = ActiveEndpoint {
+            index,
+            since: Instant::now(),
+        };
+    }
 }
 
 type Result<T> = std::result::Result<T, errors::Error>;
@@ -31,91 +199,260 @@ impl Client {
     /// ensure that the URL is valid. The user must check the status of the API by calling the
     /// [`get_api_status`](crate::Client::get_api_status) function.
     pub fn new(url: &str, token: &str) -> Result<Self> {
+        Self::with_options(url, token, ClientOptions::default())
+    }
+
+    /// Creates a new instance of the client configured with the provided [`ClientOptions`]
+    ///
+    /// Like [`new`](crate::Client::new), this function will not attempt to connect to the Home
+    /// Assistant instance.
+    pub fn with_options(url: &str, token: &str, options: ClientOptions) -> Result<Self> {
+        Self::with_urls([url], token, options)
+    }
+
+    /// Creates a new instance of the client that fails over between several base URLs
+    ///
+    /// The URLs are listed in order of preference, for example Home Assistant's `internal_url`
+    /// followed by its `external_url`. Requests are sent to the active URL, which starts out as
+    /// the first one. If it cannot be reached, the client fails over to the next reachable URL
+    /// and periodically tries to fail back to the preferred ones, see
+    /// [`ClientOptions::failback_interval`]. The URL currently in use is returned by
+    /// [`active_url`](crate::Client::active_url).
+    pub fn with_urls<I, S>(urls: I, token: &str, options: ClientOptions) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let urls = urls
+            .into_iter()
+            .map(|url| Url::parse(url.as_ref()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        if urls.is_empty() {
+            return Err(errors::Error::NoUrls);
+        }
+
+        let connect_timeout = options
+            .connect_timeout
+            .or((urls.len() > 1).then_some(FAILOVER_CONNECT_TIMEOUT));
+
+        let mut http = reqwest::Client::builder();
+        if let Some(connect_timeout) = connect_timeout {
+            http = http.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = options.timeout {
+            http = http.timeout(timeout);
+        }
+
         Ok(Client {
-            url: Url::parse(url)?,
-            token: token.to_owned(),
+            inner: Arc::new(ClientInner {
+                endpoints: Endpoints::new(urls, options.failback_interval),
+                token: token.to_owned(),
+                http: http.build()?,
+                options,
+            }),
         })
     }
 
-    fn build_get_request(&self, endpoint: &str) -> RequestBuilder {
-        let mut url = self.url.clone();
-        url.set_path(endpoint);
+    /// Returns the base URLs of the instance in order of preference
+    pub fn urls(&self) -> &[Url] {
+        &self.inner.endpoints.urls
+    }
 
-        reqwest::Client::new()
-            .get(url)
-            .bearer_auth(self.token.clone())
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
+    /// Returns the base URL that requests are currently sent to
+    pub fn active_url(&self) -> &Url {
+        &self.inner.endpoints.urls[self.inner.endpoints.active()]
     }
 
-    fn build_post_request(&self, endpoint: &str) -> RequestBuilder {
-        let mut url = self.url.clone();
-        url.set_path(endpoint);
+    /// Probes each base URL in order of preference and makes the first reachable one active
+    ///
+    /// Only reachability is checked: any HTTP response, including an authentication failure,
+    /// counts as reachable. Returns the URL that became active.
+    pub async fn detect_active_url(&self) -> Result<&Url> {
+        let mut last_error = None;
+
+        for (index, url) in self.inner.endpoints.urls.iter().enumerate() {
+            match self.build_get_request(url, "/api/").send().await {
+                Ok(_) => {
+                    self.inner.endpoints.record_success(index, true);
+                    return Ok(url);
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(last_error
+            .expect("at least one base URL is configured")
+            .into())
+    }
+
+    /// Starts a GET request to an arbitrary endpoint of the Home Assistant instance
+    ///
+    /// This is an escape hatch for endpoints this crate does not model, such as those added by
+    /// integrations. See [`raw::RawRequest`] for details.
+    pub fn raw_get(&self, path: &str) -> raw::RawRequest<'_> {
+        raw::RawRequest::new(self, Method::GET, path)
+    }
+
+    /// Starts a POST request to an arbitrary endpoint of the Home Assistant instance
+    ///
+    /// See [`raw_get`](crate::Client::raw_get) for details.
+    pub fn raw_post(&self, path: &str) -> raw::RawRequest<'_> {
+        raw::RawRequest::new(self, Method::POST, path)
+    }
 
-        reqwest::Client::new()
-            .post(url)
-            .bearer_auth(self.token.clone())
+    /// Starts a DELETE request to an arbitrary endpoint of the Home Assistant instance
+    ///
+    /// See [`raw_get`](crate::Client::raw_get) for details.
+    pub fn raw_delete(&self, path: &str) -> raw::RawRequest<'_> {
+        raw::RawRequest::new(self, Method::DELETE, path)
+    }
+
+    pub(crate) fn build_request(&self, method: Method, url: Url) -> RequestBuilder {
+        self.inner
+            .http
+            .request(method, url)
+            .bearer_auth(self.inner.token.clone())
             .header(reqwest::header::CONTENT_TYPE, "application/json")
     }
 
-    fn build_get_request_with_query(&self, query_params: get::Request) -> RequestBuilder {
-        let mut url = self.url.clone();
-        url.set_path(&query_params.endpoint);
+    fn build_get_request(&self, base: &Url, endpoint: &str) -> RequestBuilder {
+        self.build_request(Method::GET, endpoint_url(base, endpoint))
+    }
 
-        if !query_params.query.is_empty() {
-            let mut query_string = String::new();
-            let mut first_time = true;
-
-            for (key, value) in query_params.query {
-                if first_time {
-                    query_string.push_str(format!("{}={}", key, value).as_str());
-                } else {
-                    query_string.push_str(format!("&{}={}", key, value).as_str());
+    fn build_post_request(&self, base: &Url, endpoint: &str) -> RequestBuilder {
+        self.build_request(Method::POST, endpoint_url(base, endpoint))
+    }
+
+    fn build_delete_request(&self, base: &Url, endpoint: &str) -> RequestBuilder {
+        self.build_request(Method::DELETE, endpoint_url(base, endpoint))
+    }
+
+    /// Attaches `body` to the request as JSON, compressing it when configured to do so
+    ///
+    /// If the body cannot be serialized or compressed, it is handed to reqwest as-is so that the
+    /// error surfaces when the request is sent.
+    pub(crate) fn with_json_body<S: Serialize + ?Sized>(
+        &self,
+        builder: RequestBuilder,
+        body: &S,
+    ) -> RequestBuilder {
+        #[cfg(feature = "gzip")]
+        if let Some(ref compression) = self.inner.options.request_compression {
+            if let Ok(serialized) = serde_json::to_vec(body) {
+                if serialized.len() < compression.min_size {
+                    return builder.body(serialized);
+                }
+
+                if let Ok(compressed) = gzip(&serialized, compression.level) {
+                    return builder
+                        .header(reqwest::header::CONTENT_ENCODING, "gzip")
+                        .body(compressed);
                 }
-                first_time = false;
             }
+        }
 
-            url.set_query(Some(&query_string));
+        builder.json(body)
+    }
+
+    fn build_get_request_with_query(
+        &self,
+        base: &Url,
+        query_params: &get::Request,
+    ) -> RequestBuilder {
+        let mut url = endpoint_url(base, &query_params.endpoint);
+
+        if !query_params.query.is_empty() {
+            // Percent-encode the values, otherwise the `+` of timezone offsets is decoded as a space
+            url.query_pairs_mut().extend_pairs(&query_params.query);
         }
 
-        reqwest::Client::new()
+        self.inner
+            .http
             .get(url)
-            .bearer_auth(self.token.clone())
+            .bearer_auth(self.inner.token.clone())
             .header(reqwest::header::CONTENT_TYPE, "application/json")
     }
 
     fn build_post_request_with_query<S: Serialize>(
         &self,
-        query_params: post::Request<S>,
+        base: &Url,
+        query_params: &post::Request<S>,
     ) -> RequestBuilder {
-        let mut url = self.url.clone();
-        url.set_path(&query_params.endpoint);
+        let builder = self.build_post_request(base, &query_params.endpoint);
 
-        reqwest::Client::new()
-            .post(url)
-            .bearer_auth(self.token.clone())
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .json(&query_params.body)
+        self.with_json_body(builder, &query_params.body)
     }
 
-    async fn post_text_request<S: Serialize>(
+    fn build_event_request(
         &self,
-        post_param: post::Request<S>,
-    ) -> Result<String> {
-        let request = self
-            .build_post_request(&post_param.endpoint)
-            .json(&post_param.body)
-            .send()
-            .await?
-            .text()
-            .await?;
+        base: &Url,
+        request: &post::Request<Option<serde_json::Value>>,
+    ) -> RequestBuilder {
+        let builder = self.build_post_request(base, &request.endpoint);
 
-        Ok(request)
+        match request.body {
+            Some(ref data) => self.with_json_body(builder, data),
+            None => builder,
+        }
+    }
+
+    fn build_return_response_request<S: Serialize>(
+        &self,
+        base: &Url,
+        request: &post::Request<S>,
+    ) -> RequestBuilder {
+        let mut url = endpoint_url(base, &request.endpoint);
+        url.set_query(Some("return_response"));
+
+        let builder = self.build_request(Method::POST, url);
+        self.with_json_body(builder, &request.body)
+    }
+
+    /// Sends the request built by `build`, failing over between the configured base URLs
+    ///
+    /// The active URL is tried first. If it cannot be reached, the remaining URLs are tried in the
+    /// order they were provided and the first one that responds becomes the active URL. Once
+    /// [`ClientOptions::failback_interval`] has elapsed since the last failover, the preferred URLs
+    /// are tried again so that the client fails back when they become reachable.
+    ///
+    /// Requests that time out are only retried on the next URL when they are GET requests, since
+    /// the server may have handled a service call or a fired event before the timeout.
+    pub(crate) async fn send<F>(
+        &self,
+        build: F,
+    ) -> std::result::Result<reqwest::Response, reqwest::Error>
+    where
+        F: Fn(&Url) -> RequestBuilder,
+    {
+        let (order, failing_back) = self.inner.endpoints.attempt_order();
+        let mut last_error = None;
+
+        for index in order {
+            let url = &self.inner.endpoints.urls[index];
+
+            let request = build(url).build()?;
+            let retry_on_timeout = request.method() == Method::GET;
+
+            match self.inner.http.execute(request).await {
+                Ok(response) => {
+                    self.inner.endpoints.record_success(index, failing_back);
+                    return Ok(response);
+                }
+                Err(err) if err.is_connect() || (retry_on_timeout && err.is_timeout()) => {
+                    log::warn!("Unable to reach {}: {}", url, err);
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(last_error.expect("at least one base URL is configured"))
     }
 
     async fn get_text_request(&self, endpoint: &str) -> Result<String> {
         let request = self
-            .build_get_request(endpoint)
-            .send()
+            .send(|base| self.build_get_request(base, endpoint))
             .await?
             .text()
             .await?;
@@ -123,16 +460,25 @@ impl Client {
         Ok(request)
     }
 
-    #[allow(dead_code)]
-    async fn get_binary_request(&self, endpoint: &str) -> Result<Bytes> {
-        let request = self
-            .build_get_request(endpoint)
-            .send()
-            .await?
-            .bytes()
+    /// Sends a GET request and returns the raw response body along with its content type
+    async fn get_binary_request<Q>(&self, queryable: Q) -> Result<(Option<String>, Bytes)>
+    where
+        Q: get::Parameters,
+    {
+        let query_params = queryable.into_request();
+
+        let response = self
+            .send(|base| self.build_get_request_with_query(base, &query_params))
             .await?;
+        let response = error_for_status(response).await?;
 
-        Ok(request)
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+
+        Ok((content_type, response.bytes().await?))
     }
 
     /// Sends a GET request to the specified endpoint and returns the response as a deserialized object
@@ -142,8 +488,7 @@ impl Client {
         D: DeserializeOwned,
     {
         let request = self
-            .build_get_request(endpoint.as_ref())
-            .send()
+            .send(|base| self.build_get_request(base, endpoint.as_ref()))
             .await?
             .json::<D>()
             .await?;
@@ -161,8 +506,7 @@ impl Client {
         D: DeserializeOwned,
     {
         let bytes = self
-            .build_get_request(endpoint.as_ref())
-            .send()
+            .send(|base| self.build_get_request(base, endpoint.as_ref()))
             .await?
             .bytes()
             .await?;
@@ -186,8 +530,7 @@ impl Client {
         D: DeserializeOwned,
     {
         let request = self
-            .build_post_request(endpoint)
-            .send()
+            .send(|base| self.build_post_request(base, endpoint))
             .await?
             .json::<D>()
             .await?;
@@ -205,8 +548,7 @@ impl Client {
         D: DeserializeOwned,
     {
         let bytes = self
-            .build_post_request(endpoint.as_ref())
-            .send()
+            .send(|base| self.build_post_request(base, endpoint.as_ref()))
             .await?
             .bytes()
             .await?;
@@ -232,8 +574,7 @@ impl Client {
         let query_params = queryable.into_request();
 
         let request = self
-            .build_get_request_with_query(query_params)
-            .send()
+            .send(|base| self.build_get_request_with_query(base, &query_params))
             .await?
             .json::<D>()
             .await?;
@@ -250,8 +591,7 @@ impl Client {
         let query_params = queryable.into_request();
 
         let bytes = self
-            .build_get_request_with_query(query_params)
-            .send()
+            .send(|base| self.build_get_request_with_query(base, &query_params))
             .await?
             .bytes()
             .await?;
@@ -269,21 +609,6 @@ impl Client {
         Ok(response)
     }
 
-    async fn post_request_with_query<S, D>(&self, request: post::Request<S>) -> Result<D>
-    where
-        S: Serialize,
-        D: DeserializeOwned,
-    {
-        let request = self
-            .build_post_request_with_query(request)
-            .send()
-            .await?
-            .json::<D>()
-            .await?;
-
-        Ok(request)
-    }
-
     #[cfg(feature = "serde_debugging")]
     async fn post_request_with_query_and_debugging<S, D>(
         &self,
@@ -293,9 +618,10 @@ impl Client {
         S: Serialize,
         D: DeserializeOwned,
     {
-        let bytes = self
-            .build_post_request_with_query(request)
-            .send()
+        let response = self
+            .send(|base| self.build_post_request_with_query(base, &request))
+            .await?;
+        let bytes = error_for_status_with_debugging(response)
             .await?
             .bytes()
             .await?;
@@ -371,7 +697,10 @@ impl Client {
 
     /// Calls the `/api/history/period/<timestamp>` which returns an array of state changes in the past
     pub async fn get_history(&self, params: get::HistoryParams) -> Result<get::HistoryResponse> {
-        self.get_request_with_query(params).await
+        let mut history: get::HistoryResponse = self.get_request_with_query(params).await?;
+        entity_state::fill_history_entity_ids(&mut history);
+
+        Ok(history)
     }
 
     /// Same as [`get_history`] but using [`serde_path_to_error`] as the deserialize adapter
@@ -382,7 +711,87 @@ impl Client {
         &self,
         params: get::HistoryParams,
     ) -> DebuggingResult<get::HistoryResponse> {
-        self.get_request_with_query_and_debugging(params).await
+        let mut history: get::HistoryResponse =
+            self.get_request_with_query_and_debugging(params).await?;
+        entity_state::fill_history_entity_ids(&mut history);
+
+        Ok(history)
+    }
+
+    /// Same as [`get_history`] but decodes the response into a compact time series per entity
+    ///
+    /// `minimal_response` and `no_attributes` are always requested since only the state and
+    /// `last_changed` of each entry are kept.
+    ///
+    /// [`get_history`]: Client::get_history
+    pub async fn get_history_series(
+        &self,
+        params: get::HistoryParams,
+    ) -> Result<get::HistorySeriesResponse> {
+        self.get_request_with_query(get::HistoryParams {
+            minimal_response: true,
+            no_attributes: true,
+            ..params
+        })
+        .await
+    }
+
+    /// Same as [`get_history_series`] but splits the period into windows of `options.window`
+    ///
+    /// The windows are fetched with at most `options.concurrency` requests in flight and joined
+    /// into a single series per entity. The state Home Assistant repeats at the start of every
+    /// window is dropped. The period is the one Home Assistant would return for the same
+    /// parameters: `start_time` until `end_time`, or one day after `start_time` when `end_time` is
+    /// not set. A single request is sent when `start_time` is not set.
+    ///
+    /// [`get_history_series`]: Client::get_history_series
+    pub async fn get_history_chunked(
+        &self,
+        params: get::HistoryParams,
+        options: get::ChunkOptions,
+    ) -> Result<get::History> {
+        let Some(start_time) = params.start_time else {
+            return Ok(self.get_history_series(params).await?.into());
+        };
+
+        let end_time = match params.end_time {
+            Some(end_time) => end_time,
+            None => start_time
+                .checked_add_signed(TimeDelta::days(1))
+                .ok_or(errors::Error::PeriodOutOfRange)?,
+        };
+
+        let chunks = stream::iter(chunked::windows(start_time, end_time, options.window))
+            .map(|(start, end)| {
+                self.get_history_series(get::HistoryParams {
+                    filter_entity_ids: params.filter_entity_ids.clone(),
+                    start_time: Some(start),
+                    end_time: Some(end),
+                    significant_changes_only: params.significant_changes_only,
+                    ..get::HistoryParams::default()
+                })
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        Ok(chunked::stitch_history(chunks))
+    }
+
+    /// Same as [`get_history_series`] but using [`serde_path_to_error`] as the deserialize adapter
+    ///
+    /// [`get_history_series`]: Client::get_history_series
+    #[cfg(feature = "serde_debugging")]
+    pub async fn get_history_series_with_debugging(
+        &self,
+        params: get::HistoryParams,
+    ) -> DebuggingResult<get::HistorySeriesResponse> {
+        self.get_request_with_query_and_debugging(get::HistoryParams {
+            minimal_response: true,
+            no_attributes: true,
+            ..params
+        })
+        .await
     }
 
     /// Calls the `/api/logbook/<timestamp>` which returns an array of logbook entries
@@ -390,6 +799,55 @@ impl Client {
         self.get_request_with_query(params).await
     }
 
+    /// Same as [`get_logbook`] but splits the period into windows of `options.window`
+    ///
+    /// The windows are fetched with at most `options.concurrency` requests in flight and their
+    /// entries are joined in order, dropping entries returned by two consecutive windows. The
+    /// period is the one Home Assistant would return for the same parameters: `start_time` until
+    /// `end_time`, or otherwise the `period_days` days ending one day after `start_time`. A single
+    /// request is sent when `start_time` is not set.
+    ///
+    /// [`get_logbook`]: Client::get_logbook
+    pub async fn get_logbook_chunked(
+        &self,
+        params: get::LogbookParams,
+        options: get::ChunkOptions,
+    ) -> Result<get::LogbookResponse> {
+        let Some(timestamp) = params.start_time else {
+            return self.get_logbook(params).await;
+        };
+
+        let (start_time, end_time) = match params.end_time {
+            Some(end_time) => (timestamp, end_time),
+            None => {
+                let period_days = i64::from(params.period_days.unwrap_or(1));
+                let start_time = TimeDelta::try_days(period_days - 1)
+                    .and_then(|before| timestamp.checked_sub_signed(before));
+                let end_time = timestamp.checked_add_signed(TimeDelta::days(1));
+
+                start_time
+                    .zip(end_time)
+                    .ok_or(errors::Error::PeriodOutOfRange)?
+            }
+        };
+
+        let chunks = stream::iter(chunked::windows(start_time, end_time, options.window))
+            .map(|(start, end)| {
+                self.get_logbook(get::LogbookParams {
+                    entity_ids: params.entity_ids.clone(),
+                    context_id: params.context_id.clone(),
+                    start_time: Some(start),
+                    end_time: Some(end),
+                    period_days: None,
+                })
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        Ok(chunked::stitch_logbook(chunks))
+    }
+
     /// Same as [`get_logbook`] but using [`serde_path_to_error`] as the deserialize adapter
     ///
     /// [`get_logbook`]: Client::get_logbook
@@ -415,7 +873,7 @@ impl Client {
     }
 
     /// Calls the `/api/states/<entity_id>` which returns a state object for the specifies `entity_id`
-    pub async fn get_states_of_entity<D>(&self, entity_id: D) -> Result<get::StatesEntityResponse>
+    pub async fn get_states_of_entity<D>(&self, entity_id: D) -> Result<EntityState>
     where
         D: Display,
     {
@@ -430,7 +888,7 @@ impl Client {
     pub async fn get_states_of_entity_with_debugging<D>(
         &self,
         entity_id: D,
-    ) -> DebuggingResult<get::StatesEntityResponse>
+    ) -> DebuggingResult<EntityState>
     where
         D: Display,
     {
@@ -438,14 +896,98 @@ impl Client {
             .await
     }
 
+    /// Same as [`get_states_of_entity`] but returns `Ok(None)` if Home Assistant does not know the entity
+    ///
+    /// Other failures, such as an invalid token or an unreachable instance, are still returned as
+    /// errors.
+    ///
+    /// [`get_states_of_entity`]: Client::get_states_of_entity
+    pub async fn get_states_of_entity_if_exists<D>(
+        &self,
+        entity_id: D,
+    ) -> Result<Option<EntityState>>
+    where
+        D: Display,
+    {
+        let endpoint = format!("/api/states/{}", entity_id);
+
+        let response = self
+            .send(|base| self.build_get_request(base, &endpoint))
+            .await?;
+
+        match error_for_status(response).await {
+            Ok(response) => Ok(Some(response.json().await?)),
+            Err(errors::Error::ApiErrorResponse { status, .. })
+                if status == reqwest::StatusCode::NOT_FOUND =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     /// Calls the `/api/error_log` which returns all errors logged during the current session as a plaintext response.
     pub async fn get_error_log(&self) -> Result<String> {
         self.get_text_request("/api/error_log").await
     }
 
-    /// Calls the `/api/camera_proxy/<camera entity_id>`. Still a work in progress. Currently unimplemented.
-    pub async fn get_camera_proxy(&self) -> Result<Bytes> {
-        unimplemented!()
+    /// Same as [`get_error_log`] but parses the log into individual records
+    ///
+    /// [`get_error_log`]: Client::get_error_log
+    pub async fn get_error_log_entries(&self) -> Result<get::ErrorLog> {
+        let log = self.get_error_log().await?;
+
+        Ok(get::ErrorLog::parse(&log))
+    }
+
+    /// Calls the `/api/camera_proxy/<camera entity_id>` endpoint which returns the current image of a camera.
+    ///
+    /// Returns [`errors::Error::EntityNotFound`] if the camera does not exist.
+    pub async fn get_camera_proxy(
+        &self,
+        params: get::CameraProxyParams,
+    ) -> Result<get::CameraProxyResponse> {
+        let entity_id = params.entity_id.clone();
+
+        let (content_type, image) = match self.get_binary_request(params).await {
+            Err(errors::Error::ApiErrorResponse { status, .. })
+                if status == reqwest::StatusCode::NOT_FOUND =>
+            {
+                return Err(errors::Error::EntityNotFound(entity_id));
+            }
+            result => result?,
+        };
+
+        Ok(get::CameraProxyResponse {
+            content_type,
+            dimensions: image::dimensions(&image),
+            image,
+        })
+    }
+
+    /// Calls the `/api/camera_proxy_stream/<camera entity_id>` endpoint which streams MJPEG frames of a camera.
+    ///
+    /// Returns [`errors::Error::EntityNotFound`] if the camera does not exist. See [`CameraStream`]
+    /// for details on consuming and cancelling the stream.
+    pub async fn get_camera_stream(&self, params: get::CameraStreamParams) -> Result<CameraStream> {
+        let entity_id = params.entity_id.clone();
+        let min_frame_interval = params.min_frame_interval;
+        let query_params = params.into_request();
+
+        let response = self
+            .send(|base| self.build_get_request_with_query(base, &query_params))
+            .await?;
+
+        let response = match error_for_status(response).await {
+            Err(errors::Error::ApiErrorResponse { status, .. })
+                if status == reqwest::StatusCode::NOT_FOUND =>
+            {
+                return Err(errors::Error::EntityNotFound(entity_id));
+            }
+            result => result?,
+        };
+
+        CameraStream::new(response, min_frame_interval)
     }
 
     /// Calls the `/api/calendars` endpoint which returns an array of calendar entities.
@@ -480,39 +1022,75 @@ impl Client {
         self.get_request_with_query_and_debugging(params).await
     }
 
-    // Calls the `/api/states/<entity_id>` endpoint which updates or creates a state.
-    pub async fn post_states(&self, params: post::StateParams) -> Result<post::StateResponse> {
-        self.post_request_with_query(params.into_request()).await
+    /// Calls the `/api/states/<entity_id>` endpoint which creates or updates the state of an entity.
+    ///
+    /// [`post::StateUpdateResponse::created`] tells whether the entity was created (HTTP 201) or an
+    /// existing entity was updated (HTTP 200).
+    pub async fn post_states<A>(
+        &self,
+        params: post::StateParams<A>,
+    ) -> Result<post::StateUpdateResponse>
+    where
+        A: Serialize,
+    {
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_post_request_with_query(base, &request))
+            .await?;
+        let response = error_for_status(response).await?;
+        let created = response.status() == reqwest::StatusCode::CREATED;
+
+        Ok(post::StateUpdateResponse {
+            created,
+            state: response.json().await?,
+        })
     }
 
     /// Same as [`post_states`] but using [`serde_path_to_error`] as the deserialize adapter
     ///
     /// [`post_states`]: Client::post_states
     #[cfg(feature = "serde_debugging")]
-    pub async fn post_states_with_debugging(
+    pub async fn post_states_with_debugging<A>(
         &self,
-        params: post::StateParams,
-    ) -> DebuggingResult<post::StateResponse> {
-        self.post_request_with_query_and_debugging(params.into_request())
-            .await
+        params: post::StateParams<A>,
+    ) -> DebuggingResult<post::StateUpdateResponse>
+    where
+        A: Serialize,
+    {
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_post_request_with_query(base, &request))
+            .await?;
+        let response = error_for_status_with_debugging(response).await?;
+        let created = response.status() == reqwest::StatusCode::CREATED;
+        let bytes = response.bytes().await?;
+
+        let state = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(
+            bytes.as_ref(),
+        ))
+        .map_err(|err| errors::DebuggingError::DeserializeFailed {
+            error: err,
+            response: std::str::from_utf8(bytes.as_ref())
+                .unwrap_or("Failed to convert bytes to string")
+                .to_string(),
+        })?;
+
+        Ok(post::StateUpdateResponse { created, state })
     }
 
     /// Calls the `/api/events/<event_type>` endpoint which fires an event.
     pub async fn post_events(&self, params: post::EventParams) -> Result<post::EventResponse> {
         let request = params.into_request();
-        let builder = self.build_post_request(&request.endpoint);
-
-        Ok(match request.body {
-            Some(data) => {
-                builder
-                    .json(&data)
-                    .send()
-                    .await?
-                    .json::<post::EventResponse>()
-                    .await?
-            }
-            None => builder.send().await?.json::<post::EventResponse>().await?,
-        })
+
+        let response = self
+            .send(|base| self.build_event_request(base, &request))
+            .await?
+            .json::<post::EventResponse>()
+            .await?;
+
+        Ok(response)
     }
 
     /// Same as [`post_events`] but using [`serde_path_to_error`] as the deserialize adapter
@@ -524,12 +1102,12 @@ impl Client {
         params: post::EventParams,
     ) -> DebuggingResult<post::EventResponse> {
         let request = params.into_request();
-        let builder = self.build_post_request(&request.endpoint);
 
-        let bytes = match request.body {
-            Some(data) => builder.json(&data).send().await?.bytes().await?,
-            None => builder.send().await?.bytes().await?,
-        };
+        let bytes = self
+            .send(|base| self.build_event_request(base, &request))
+            .await?
+            .bytes()
+            .await?;
 
         let response = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(
             bytes.as_ref(),
@@ -544,17 +1122,130 @@ impl Client {
         Ok(response)
     }
 
-    /// Calls the `/api/services/<domain>/<service>` endpoint which calls a service. Currently unimplemented.
-    pub async fn post_service(&self) -> Result<()> {
-        unimplemented!()
+    /// Calls the `/api/services/<domain>/<service>` endpoint which calls a service.
+    ///
+    /// Returns the states that changed while the service was being executed. Errors reported by
+    /// Home Assistant, such as an unknown service, are returned as
+    /// [`errors::Error::ApiErrorResponse`].
+    pub async fn post_service(&self, params: post::ServiceParams) -> Result<post::ServiceResponse> {
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_post_request_with_query(base, &request))
+            .await?;
+
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// Same as [`post_service`] but using [`serde_path_to_error`] as the deserialize adapter
+    ///
+    /// [`post_service`]: Client::post_service
+    #[cfg(feature = "serde_debugging")]
+    pub async fn post_service_with_debugging(
+        &self,
+        params: post::ServiceParams,
+    ) -> DebuggingResult<post::ServiceResponse> {
+        self.post_request_with_query_and_debugging(params.into_request())
+            .await
+    }
+
+    /// Calls the `/api/services/<domain>/<service>?return_response` endpoint which calls a service
+    /// that returns data, such as `weather.get_forecasts` or `calendar.get_events`.
+    ///
+    /// The `service_response` is deserialized into `T` and returned alongside the states that
+    /// changed. Home Assistant rejects the call with a [`errors::Error::ApiErrorResponse`] if the
+    /// service does not support returning a response.
+    pub async fn post_service_with_response<T>(
+        &self,
+        params: post::ServiceParams,
+    ) -> Result<post::ServiceCallResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_return_response_request(base, &request))
+            .await?;
+
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// Same as [`post_service_with_response`] but using [`serde_path_to_error`] as the deserialize adapter
+    ///
+    /// [`post_service_with_response`]: Client::post_service_with_response
+    #[cfg(feature = "serde_debugging")]
+    pub async fn post_service_with_response_and_debugging<T>(
+        &self,
+        params: post::ServiceParams,
+    ) -> DebuggingResult<post::ServiceCallResponse<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_return_response_request(base, &request))
+            .await?;
+        let bytes = error_for_status_with_debugging(response)
+            .await?
+            .bytes()
+            .await?;
+
+        let response = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(
+            bytes.as_ref(),
+        ))
+        .map_err(|err| errors::DebuggingError::DeserializeFailed {
+            error: err,
+            response: std::str::from_utf8(bytes.as_ref())
+                .unwrap_or("Failed to convert bytes to string")
+                .to_string(),
+        })?;
+
+        Ok(response)
     }
 
     /// Calls the `/api/template` endpoint which renders a Home Assistant template.
+    ///
+    /// Returns [`errors::Error::TemplateError`] with Home Assistant's message if the template
+    /// cannot be rendered.
     pub async fn post_template(&self, params: post::TemplateParams) -> Result<String> {
-        self.post_text_request(params.into_request()).await
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_post_request_with_query(base, &request))
+            .await?;
+
+        match error_for_status(response).await {
+            Ok(response) => Ok(response.text().await?),
+            Err(errors::Error::ApiErrorResponse { status, message })
+                if status == reqwest::StatusCode::BAD_REQUEST =>
+            {
+                Err(errors::Error::TemplateError(message))
+            }
+            Err(err) => Err(err),
+        }
     }
 
-    /// Calls the `/api/config/core/check_config` endpoint which triggers a check of the current configuration. Currently unimplemented.
+    /// Same as [`post_template`] but parses the rendered output into `T`
+    ///
+    /// The output is parsed as JSON when possible, so templates rendering numbers, booleans, lists
+    /// or objects can be read into the matching Rust types, including [`StateEnum`](crate::StateEnum).
+    /// Outputs that are not valid JSON are treated as strings.
+    ///
+    /// [`post_template`]: Client::post_template
+    pub async fn post_template_as<T>(&self, params: post::TemplateParams) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let rendered = self.post_template(params).await?;
+
+        Ok(post::parse_rendered_template(&rendered)?)
+    }
+
+    /// Calls the `/api/config/core/check_config` endpoint which triggers a check of the current configuration.
+    ///
+    /// Use [`post::CheckConfigResponse::ensure_valid`] to fail when the configuration is invalid.
     pub async fn post_config_check(&self) -> Result<post::CheckConfigResponse> {
         self.post_request("/api/config/core/check_config").await
     }
@@ -570,8 +1261,143 @@ impl Client {
             .await
     }
 
-    /// Calls the `/api/intent/handle` endpoint which handles an intent. Currently unimplemented.
-    pub async fn post_handle(&self) -> Result<()> {
-        unimplemented!()
+    /// Calls the `/api/conversation/process` endpoint which processes a sentence with a conversation agent.
+    ///
+    /// Use a [`ConversationSession`](crate::ConversationSession) to carry the conversation id across turns.
+    pub async fn post_conversation(
+        &self,
+        params: post::ConversationParams,
+    ) -> Result<post::ConversationResponse> {
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_post_request_with_query(base, &request))
+            .await?;
+
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// Same as [`post_conversation`] but using [`serde_path_to_error`] as the deserialize adapter
+    ///
+    /// [`post_conversation`]: Client::post_conversation
+    #[cfg(feature = "serde_debugging")]
+    pub async fn post_conversation_with_debugging(
+        &self,
+        params: post::ConversationParams,
+    ) -> DebuggingResult<post::ConversationResponse> {
+        self.post_request_with_query_and_debugging(params.into_request())
+            .await
+    }
+
+    /// Calls the `/api/intent/handle` endpoint which handles an intent.
+    ///
+    /// Requires the `intent` integration to be enabled in Home Assistant.
+    pub async fn post_handle(&self, params: post::IntentParams) -> Result<post::IntentResponse> {
+        let request = params.into_request();
+
+        let response = self
+            .send(|base| self.build_post_request_with_query(base, &request))
+            .await?;
+
+        Ok(error_for_status(response).await?.json().await?)
+    }
+
+    /// Same as [`post_handle`] but using [`serde_path_to_error`] as the deserialize adapter
+    ///
+    /// [`post_handle`]: Client::post_handle
+    #[cfg(feature = "serde_debugging")]
+    pub async fn post_handle_with_debugging(
+        &self,
+        params: post::IntentParams,
+    ) -> DebuggingResult<post::IntentResponse> {
+        self.post_request_with_query_and_debugging(params.into_request())
+            .await
     }
+
+    /// Calls the `/api/states/<entity_id>` endpoint which removes the state of an entity.
+    ///
+    /// Returns `true` if the entity existed and was removed, or `false` if Home Assistant did not
+    /// know the entity.
+    pub async fn delete_state<D>(&self, entity_id: D) -> Result<bool>
+    where
+        D: Display,
+    {
+        let endpoint = format!("/api/states/{}", entity_id);
+
+        let response = self
+            .send(|base| self.build_delete_request(base, &endpoint))
+            .await?;
+
+        match error_for_status(response).await {
+            Ok(_) => Ok(true),
+            Err(errors::Error::ApiErrorResponse { status, .. })
+                if status == reqwest::StatusCode::NOT_FOUND =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Appends `endpoint` to the path of `base` so that instances served under a path prefix, for
+/// example behind a reverse proxy, are supported
+pub(crate) fn endpoint_url(base: &Url, endpoint: &str) -> Url {
+    let mut url = base.clone();
+    url.set_path(&format!(
+        "{}/{}",
+        base.path().trim_end_matches('/'),
+        endpoint.trim_start_matches('/')
+    ));
+    url
+}
+
+/// Maps responses with an error status code to [`errors::Error::ApiErrorResponse`]
+///
+/// Home Assistant usually describes the error in a JSON body of the form `{"message": "..."}`.
+/// When the body has a different shape, it is used as the message verbatim.
+pub(crate) async fn error_for_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(response);
+    }
+
+    let message = error_message(response).await?;
+    Err(errors::Error::ApiErrorResponse { status, message })
+}
+
+/// Same as [`error_for_status`] but mapping to [`errors::DebuggingError::ApiErrorResponse`]
+#[cfg(feature = "serde_debugging")]
+async fn error_for_status_with_debugging(
+    response: reqwest::Response,
+) -> DebuggingResult<reqwest::Response> {
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(response);
+    }
+
+    let message = error_message(response).await?;
+    Err(errors::DebuggingError::ApiErrorResponse { status, message })
+}
+
+/// Extracts the error message from the body of an error response
+async fn error_message(response: reqwest::Response) -> std::result::Result<String, reqwest::Error> {
+    let body = response.text().await?;
+    #[derive(serde::Deserialize)]
+    struct ErrorMessage {
+        message: String,
+    }
+
+    Ok(serde_json::from_str::<ErrorMessage>(&body)
+        .map(|response| response.message)
+        .unwrap_or(body))
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(data: &[u8], level: u32) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+    encoder.write_all(data)?;
+    encoder.finish()
 }
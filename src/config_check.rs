@@ -0,0 +1,181 @@
+//! Parsing of the error and warning messages returned by `/api/config/core/check_config`
+
+use crate::post::ConfigIssue;
+
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes the newline separated messages Home Assistant returns into individual issues
+///
+/// Home Assistant sends `null` when there is nothing to report, which becomes an empty list.
+pub(crate) fn deserialize_issues<'de, D>(deserializer: D) -> Result<Vec<ConfigIssue>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = Option::<String>::deserialize(deserializer)?;
+
+    Ok(text.as_deref().map(parse_issues).unwrap_or_default())
+}
+
+/// Splits `text` into one issue per message
+///
+/// Each message starts on a new line. Indented lines continue the previous message, which is how
+/// Home Assistant formats nested schema errors.
+pub(crate) fn parse_issues(text: &str) -> Vec<ConfigIssue> {
+    let mut messages: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match messages.last_mut() {
+            Some(message) if line.starts_with(char::is_whitespace) => {
+                message.push('\n');
+                message.push_str(line.trim_end());
+            }
+            _ => messages.push(line.trim().to_owned()),
+        }
+    }
+
+    messages.into_iter().map(parse_issue).collect()
+}
+
+fn parse_issue(message: String) -> ConfigIssue {
+    let (file, line) = location(&message).unwrap_or_default();
+
+    ConfigIssue {
+        integration: integration(&message),
+        file,
+        line,
+        message,
+    }
+}
+
+/// Extracts the integration a message refers to from the formats Home Assistant uses, such as
+/// `Invalid config for 'sensor' ...`, `Invalid config for [sensor]: ...`,
+/// `Platform error 'weather' from integration 'darksky' - ...`, `Platform error weather.darksky - ...`
+/// and `Integration error: darksky - ...`
+fn integration(message: &str) -> Option<String> {
+    if let Some(rest) = message.strip_prefix("Invalid config for ") {
+        return delimited(rest);
+    }
+
+    if let Some(rest) = message.strip_prefix("Platform error ") {
+        if let Some(index) = rest.find("from integration ") {
+            return delimited(&rest[index + "from integration ".len()..]);
+        }
+
+        let platform = rest.split_whitespace().next()?;
+        return platform
+            .split_once('.')
+            .map(|(_, integration)| integration.to_owned());
+    }
+
+    ["Integration error: ", "Component error: "]
+        .iter()
+        .find_map(|prefix| message.strip_prefix(prefix))
+        .and_then(|rest| rest.split(" - ").next())
+        .map(|integration| integration.trim().to_owned())
+        .filter(|integration| !integration.is_empty())
+}
+
+/// Returns the text between the leading quote or bracket of `text` and its closing counterpart
+fn delimited(text: &str) -> Option<String> {
+    let close = match text.chars().next()? {
+        '\'' => '\'',
+        '"' => '"',
+        '[' => ']',
+        _ => return None,
+    };
+
+    let rest = &text[1..];
+    rest.find(close).map(|end| rest[..end].to_owned())
+}
+
+/// Extracts the file and line from `... at configuration.yaml, line 12` or
+/// `... (See /config/configuration.yaml, line 12)`
+fn location(message: &str) -> Option<(Option<String>, Option<u32>)> {
+    let index = message.find(", line ")?;
+
+    let line = message[index + ", line ".len()..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .ok();
+
+    let before = &message[..index];
+    let start = ["See ", " at "]
+        .iter()
+        .filter_map(|marker| before.rfind(marker).map(|i| i + marker.len()))
+        .max()?;
+
+    // Home Assistant reports `?` when it does not know where the configuration came from
+    let file = Some(before[start..].trim())
+        .filter(|file| !file.is_empty() && *file != "?")
+        .map(str::to_owned);
+
+    Some((file, line))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_invalid_config_with_location() {
+        let issues = parse_issues(
+            "Invalid config for 'sensor' at configuration.yaml, line 12: required key 'platform' not provided",
+        );
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].integration.as_deref(), Some("sensor"));
+        assert_eq!(issues[0].file.as_deref(), Some("configuration.yaml"));
+        assert_eq!(issues[0].line, Some(12));
+    }
+
+    #[test]
+    fn test_legacy_invalid_config_with_location() {
+        let issues = parse_issues(
+            "Invalid config for [automation]: expected dict. Got None. (See /config/configuration.yaml, line 7).",
+        );
+
+        assert_eq!(issues[0].integration.as_deref(), Some("automation"));
+        assert_eq!(
+            issues[0].file.as_deref(),
+            Some("/config/configuration.yaml")
+        );
+        assert_eq!(issues[0].line, Some(7));
+    }
+
+    #[test]
+    fn test_platform_errors() {
+        let issues = parse_issues(
+            "Platform error weather.darksky - Integration 'darksky' not found.\n\
+             Platform error 'light' from integration 'hue' - Integration 'hue' not found.",
+        );
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].integration.as_deref(), Some("darksky"));
+        assert_eq!(issues[0].file, None);
+        assert_eq!(issues[0].line, None);
+        assert_eq!(issues[1].integration.as_deref(), Some("hue"));
+    }
+
+    #[test]
+    fn test_unknown_location_and_continuation_lines() {
+        let issues = parse_issues(
+            "Integration error: foo - Integration 'foo' not found.\n\
+             Invalid config for [mqtt]: (See ?, line ?).\n  extra keys not allowed @ data['bar']",
+        );
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].integration.as_deref(), Some("foo"));
+        assert_eq!(issues[1].integration.as_deref(), Some("mqtt"));
+        assert_eq!(issues[1].file, None);
+        assert_eq!(issues[1].line, None);
+        assert!(issues[1]
+            .message
+            .ends_with("\n  extra keys not allowed @ data['bar']"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Multi-turn conversations with Home Assistant's Assist
+
+use crate::{errors, post, Client};
+
+type Result<T> = std::result::Result<T, errors::Error>;
+
+/// Carries the conversation id returned by Home Assistant across the turns of a conversation
+///
+/// ```rust,no_run
+/// use home_assistant_rest::{Client, ConversationSession};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::new("http://localhost:8123", "TOKEN")?;
+/// let mut session = ConversationSession::new(client).with_language("en");
+///
+/// let response = session.process("Add milk to my shopping list").await?;
+/// if response.continue_conversation {
+///     session.process("And eggs").await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConversationSession {
+    client: Client,
+    language: Option<String>,
+    agent_id: Option<String>,
+    conversation_id: Option<String>,
+}
+
+impl ConversationSession {
+    /// Starts a new conversation with Home Assistant's default agent
+    pub fn new(client: Client) -> Self {
+        ConversationSession {
+            client,
+            language: None,
+            agent_id: None,
+            conversation_id: None,
+        }
+    }
+
+    /// Sets the language of the conversation
+    pub fn with_language<S: Into<String>>(mut self, language: S) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets the conversation agent that processes every turn
+    pub fn with_agent_id<S: Into<String>>(mut self, agent_id: S) -> Self {
+        self.agent_id = Some(agent_id.into());
+        self
+    }
+
+    /// Returns the id of the conversation once Home Assistant has assigned one
+    pub fn conversation_id(&self) -> Option<&str> {
+        self.conversation_id.as_deref()
+    }
+
+    /// Forgets the conversation id so that the next turn starts a new conversation
+    pub fn reset(&mut self) {
+        self.conversation_id = None;
+    }
+
+    /// Sends the next turn of the conversation
+    ///
+    /// The conversation id returned by Home Assistant is remembered and sent with the following
+    /// turns.
+    pub async fn process(&mut self, text: &str) -> Result<post::ConversationResponse> {
+        let response = self
+            .client
+            .post_conversation(post::ConversationParams {
+                text: text.to_owned(),
+                language: self.language.clone(),
+                agent_id: self.agent_id.clone(),
+                conversation_id: self.conversation_id.clone(),
+            })
+            .await?;
+
+        if response.conversation_id.is_some() {
+            self.conversation_id.clone_from(&response.conversation_id);
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::StateEnum;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Deserializer};
+
+/// Context of the event that caused a state change
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StateContext {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub user_id: Option<String>,
+}
+
+/// State of an entity as returned by the states, history and service endpoints
+///
+/// Some endpoints leave fields out: `/api/history` omits everything but the state and
+/// `last_changed` from minimal responses, and older versions of Home Assistant do not report
+/// `last_reported` or the context. Missing attributes are left empty and a missing
+/// `last_updated` falls back to `last_changed`.
+#[derive(Debug, Clone)]
+pub struct EntityState {
+    pub entity_id: String,
+
+    pub state: Option<StateEnum>,
+
+    pub attributes: HashMap<String, serde_json::Value>,
+
+    /// Last time the state changed
+    pub last_changed: DateTime<FixedOffset>,
+
+    /// Last time the state or its attributes changed
+    pub last_updated: DateTime<FixedOffset>,
+
+    /// Last time the state was written, even if nothing changed
+    pub last_reported: Option<DateTime<FixedOffset>>,
+
+    pub context: Option<StateContext>,
+}
+
+impl EntityState {
+    /// Returns the domain of the entity, for example `sensor` for `sensor.outside_temperature`
+    pub fn domain(&self) -> &str {
+        self.entity_id
+            .split_once('.')
+            .map_or(self.entity_id.as_str(), |(domain, _)| domain)
+    }
+
+    /// Returns the object id of the entity, for example `outside_temperature` for
+    /// `sensor.outside_temperature`
+    pub fn object_id(&self) -> &str {
+        self.entity_id
+            .split_once('.')
+            .map_or(self.entity_id.as_str(), |(_, object_id)| object_id)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&serde_json::Value> {
+        self.attributes.get(name)
+    }
+
+    pub fn friendly_name(&self) -> Option<&str> {
+        self.str_attribute("friendly_name")
+    }
+
+    pub fn unit_of_measurement(&self) -> Option<&str> {
+        self.str_attribute("unit_of_measurement")
+    }
+
+    pub fn device_class(&self) -> Option<&str> {
+        self.str_attribute("device_class")
+    }
+
+    pub fn icon(&self) -> Option<&str> {
+        self.str_attribute("icon")
+    }
+
+    fn str_attribute(&self, name: &str) -> Option<&str> {
+        self.attribute(name).and_then(serde_json::Value::as_str)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawEntityState {
+    #[serde(default)]
+    entity_id: String,
+
+    state: Option<StateEnum>,
+
+    #[serde(default)]
+    attributes: Option<HashMap<String, serde_json::Value>>,
+
+    last_changed: DateTime<FixedOffset>,
+
+    #[serde(default)]
+    last_updated: Option<DateTime<FixedOffset>>,
+
+    #[serde(default)]
+    last_reported: Option<DateTime<FixedOffset>>,
+
+    #[serde(default)]
+    context: Option<StateContext>,
+}
+
+impl<'de> Deserialize<'de> for EntityState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawEntityState::deserialize(deserializer)?;
+
+        Ok(EntityState {
+            entity_id: raw.entity_id,
+            state: raw.state,
+            attributes: raw.attributes.unwrap_or_default(),
+            last_changed: raw.last_changed,
+            last_updated: raw.last_updated.unwrap_or(raw.last_changed),
+            last_reported: raw.last_reported,
+            context: raw.context,
+        })
+    }
+}
+
+/// Fills in the entity id of history entries
+///
+/// Minimal history responses only include the entity id in the first entry of each entity.
+pub(crate) fn fill_history_entity_ids(history: &mut [Vec<EntityState>]) {
+    for entries in history {
+        let Some(entity_id) = entries.first().map(|entry| entry.entity_id.clone()) else {
+            continue;
+        };
+
+        for entry in entries
+            .iter_mut()
+            .filter(|entry| entry.entity_id.is_empty())
+        {
+            entry.entity_id.clone_from(&entity_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        let state: EntityState = serde_json::from_value(json!({
+            "entity_id": "sensor.outside_temperature",
+            "state": "21.5",
+            "attributes": {
+                "friendly_name": "Outside",
+                "unit_of_measurement": "°C",
+                "device_class": "temperature",
+                "icon": "mdi:thermometer"
+            },
+            "last_changed": "2024-05-01T12:00:00+00:00"
+        }))
+        .unwrap();
+
+        assert_eq!(state.domain(), "sensor");
+        assert_eq!(state.object_id(), "outside_temperature");
+        assert_eq!(state.friendly_name(), Some("Outside"));
+        assert_eq!(state.unit_of_measurement(), Some("°C"));
+        assert_eq!(state.device_class(), Some("temperature"));
+        assert_eq!(state.icon(), Some("mdi:thermometer"));
+        assert_eq!(state.last_updated, state.last_changed);
+        assert_eq!(state.last_reported, None);
+        assert_eq!(state.context, None);
+    }
+
+    #[test]
+    fn test_fill_history_entity_ids() {
+        let mut history: Vec<Vec<EntityState>> = serde_json::from_value(json!([[
+            {
+                "entity_id": "light.kitchen",
+                "state": "on",
+                "attributes": {},
+                "last_changed": "2024-05-01T12:00:00+00:00"
+            },
+            {"state": "off", "last_changed": "2024-05-01T13:00:00+00:00"}
+        ]]))
+        .unwrap();
+
+        fill_history_entity_ids(&mut history);
+
+        assert_eq!(history[0][1].entity_id, "light.kitchen");
+        assert!(history[0][1].attributes.is_empty());
+    }
+}
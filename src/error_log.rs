@@ -0,0 +1,177 @@
+//! Parsing of the plaintext log returned by `/api/error_log`
+
+use crate::get::{ErrorLogEntry, LogLevel};
+
+use chrono::NaiveDateTime;
+
+/// Splits `log` into records, see [`ErrorLog::parse`](crate::get::ErrorLog::parse)
+pub(crate) fn parse(log: &str) -> Vec<ErrorLogEntry> {
+    let mut entries: Vec<ErrorLogEntry> = Vec::new();
+
+    for line in log.lines() {
+        if let Some(entry) = parse_record(line) {
+            entries.push(entry);
+            continue;
+        }
+
+        if let Some(entry) = entries.last_mut() {
+            let traceback = entry.traceback.get_or_insert_with(String::new);
+            if !traceback.is_empty() {
+                traceback.push('\n');
+            }
+            traceback.push_str(line);
+        }
+    }
+
+    for entry in &mut entries {
+        if let Some(ref mut traceback) = entry.traceback {
+            let trimmed = traceback.trim_end().len();
+            traceback.truncate(trimmed);
+        }
+
+        if entry.traceback.as_deref() == Some("") {
+            entry.traceback = None;
+        }
+    }
+
+    entries
+}
+
+/// Parses the first line of a record
+///
+/// Current versions of Home Assistant log
+/// `2024-05-01 12:34:56.789 ERROR (MainThread) [homeassistant.components.sensor] message`, older
+/// versions log `15-12-20 11:02:50 homeassistant.components.recorder: message`.
+fn parse_record(line: &str) -> Option<ErrorLogEntry> {
+    let mut parts = line.splitn(3, ' ');
+    let date = parts.next()?;
+    let time = parts.next()?;
+    let rest = parts.next().unwrap_or_default();
+
+    let timestamp = timestamp(date, time)?;
+
+    let (level, rest) = match rest
+        .split_once(' ')
+        .and_then(|(level, rest)| Some((LogLevel::parse(level)?, rest)))
+    {
+        Some((level, rest)) => (Some(level), rest.trim_start()),
+        None => (None, rest),
+    };
+
+    let (thread, rest) = match rest.strip_prefix('(') {
+        Some(thread) => {
+            let (thread, rest) = thread.split_once(')')?;
+            (Some(thread.to_owned()), rest.trim_start())
+        }
+        None => (None, rest),
+    };
+
+    let (logger, message) = match rest.strip_prefix('[') {
+        Some(logger) => logger.split_once(']')?,
+        None => rest.split_once(':')?,
+    };
+
+    Some(ErrorLogEntry {
+        timestamp,
+        level,
+        thread,
+        integration: integration(logger),
+        logger: logger.to_owned(),
+        message: message.trim().to_owned(),
+        traceback: None,
+    })
+}
+
+fn timestamp(date: &str, time: &str) -> Option<NaiveDateTime> {
+    let timestamp = format!("{} {}", date, time);
+
+    ["%Y-%m-%d %H:%M:%S%.f", "%y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&timestamp, format).ok())
+}
+
+/// Derives the integration from loggers such as `homeassistant.components.hue.light` or
+/// `custom_components.hacs`
+fn integration(logger: &str) -> Option<String> {
+    logger
+        .strip_prefix("homeassistant.components.")
+        .or_else(|| logger.strip_prefix("custom_components."))
+        .and_then(|rest| rest.split('.').next())
+        .filter(|integration| !integration.is_empty())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_current_format_with_traceback() {
+        let entries = parse(
+            "2024-05-01 12:34:56.789 ERROR (MainThread) [homeassistant.components.hue.light] Error updating lights\n\
+             Traceback (most recent call last):\n  \
+               File \"/usr/src/homeassistant/homeassistant/components/hue/light.py\", line 42, in update\n\
+             TimeoutError\n\
+             2024-05-01 12:35:00.001 WARNING (Recorder) [custom_components.hacs] Slow query\n",
+        );
+
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(
+            entries[0].timestamp,
+            NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                NaiveTime::from_hms_milli_opt(12, 34, 56, 789).unwrap()
+            )
+        );
+        assert_eq!(entries[0].level, Some(LogLevel::Error));
+        assert_eq!(entries[0].thread.as_deref(), Some("MainThread"));
+        assert_eq!(entries[0].logger, "homeassistant.components.hue.light");
+        assert_eq!(entries[0].integration.as_deref(), Some("hue"));
+        assert_eq!(entries[0].message, "Error updating lights");
+        assert_eq!(
+            entries[0].traceback.as_deref(),
+            Some(
+                "Traceback (most recent call last):\n  \
+                 File \"/usr/src/homeassistant/homeassistant/components/hue/light.py\", line 42, in update\n\
+                 TimeoutError"
+            )
+        );
+
+        assert_eq!(entries[1].level, Some(LogLevel::Warning));
+        assert_eq!(entries[1].thread.as_deref(), Some("Recorder"));
+        assert_eq!(entries[1].integration.as_deref(), Some("hacs"));
+        assert_eq!(entries[1].traceback, None);
+    }
+
+    #[test]
+    fn test_legacy_format() {
+        let entries = parse(
+            "15-12-20 11:03:03 netdisco.ssdp: Error fetching description at http://192.168.1.1:8200/rootDesc.xml",
+        );
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, None);
+        assert_eq!(entries[0].thread, None);
+        assert_eq!(entries[0].logger, "netdisco.ssdp");
+        assert_eq!(entries[0].integration, None);
+        assert_eq!(
+            entries[0].message,
+            "Error fetching description at http://192.168.1.1:8200/rootDesc.xml"
+        );
+    }
+
+    #[test]
+    fn test_leading_lines_are_dropped() {
+        let entries = parse(
+            "  File \"truncated.py\", line 1\n\
+             2024-05-01 12:34:56.789 INFO (MainThread) [homeassistant.setup] Setting up http",
+        );
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].level, Some(LogLevel::Info));
+        assert_eq!(entries[0].integration, None);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::post;
+
+use std::collections::BTreeMap;
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +14,58 @@ pub enum Error {
 
     #[error("Unable to deserialize the received value: {0}")]
     DeserializeFailed(#[from] serde_json::error::Error),
+
+    #[error("Unable to serialize the request body: {0}")]
+    SerializeFailed(serde_json::error::Error),
+
+    #[error("API returned an error response ({status}): {message}")]
+    ApiErrorResponse {
+        status: reqwest::StatusCode,
+        message: String,
+    },
+
+    #[error("Unable to render the template: {0}")]
+    TemplateError(String),
+
+    #[error("Configuration is invalid: {}", join_issues(.0))]
+    InvalidConfig(Vec<post::ConfigIssue>),
+
+    #[error("Entity \"{0}\" was not found")]
+    EntityNotFound(String),
+
+    #[error("Received an unexpected response: {0}")]
+    UnexpectedResponse(String),
+
+    #[error("At least one base URL must be provided")]
+    NoUrls,
+
+    #[error("No instance named \"{0}\" is registered")]
+    UnknownInstance(String),
+
+    #[error("The requested period is outside the range of supported dates")]
+    PeriodOutOfRange,
+}
+
+fn join_issues(issues: &[post::ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Aggregated failures from running an operation across a [`Registry`](crate::Registry)
+#[derive(Debug, thiserror::Error)]
+#[error("{} instance(s) failed{}", .failures.len(), join_failures(.failures))]
+pub struct RegistryError {
+    pub failures: BTreeMap<String, Error>,
+}
+
+fn join_failures(failures: &BTreeMap<String, Error>) -> String {
+    failures
+        .iter()
+        .map(|(name, error)| format!("; {}: {}", name, error))
+        .collect()
 }
 
 #[cfg(feature = "serde_debugging")]
@@ -24,6 +80,9 @@ pub enum DebuggingError {
         response: String,
     },
 
-    #[error("API returned an error response: {0}")]
-    ApiErrorResponse(String),
+    #[error("API returned an error response ({status}): {message}")]
+    ApiErrorResponse {
+        status: reqwest::StatusCode,
+        message: String,
+    },
 }
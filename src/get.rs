@@ -1,8 +1,17 @@
-use crate::StateEnum;
+use crate::{error_log, service_schema, EntityState, StateEnum};
 
-use std::collections::HashMap;
+pub use crate::history_series::{History, HistorySeries, StateInterval};
+pub use crate::service_schema::{
+    AreaSelector, ColorTempSelector, DeviceFilter, DeviceSelector, DurationSelector, EntityFilter,
+    EntitySelector, NumberSelector, NumberSelectorMode, ResponseSupport, SelectOption,
+    SelectSelector, SelectSelectorMode, Selector, ServiceField, ServiceFieldEntry,
+    ServiceResponseSchema, ServiceSchema, ServiceSection, TargetSelector, TextSelector,
+};
 
-use chrono::{DateTime, FixedOffset, NaiveDate};
+use std::{collections::HashMap, time::Duration};
+
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeDelta};
 use serde::Deserialize;
 
 #[derive(Debug)]
@@ -26,6 +35,50 @@ pub struct UnitSystemConfig {
     pub mass: String,
     pub temperature: String,
     pub volume: String,
+
+    #[serde(default)]
+    pub accumulated_precipitation: Option<String>,
+
+    #[serde(default)]
+    pub area: Option<String>,
+
+    #[serde(default)]
+    pub pressure: Option<String>,
+
+    #[serde(default)]
+    pub wind_speed: Option<String>,
+
+    /// Units that are not modeled above, keyed by their name
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
+}
+
+/// Lifecycle state of the Home Assistant core
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CoreState {
+    NotRunning,
+    Starting,
+    Running,
+    Stopping,
+    FinalWrite,
+    Stopped,
+
+    #[serde(other)]
+    Unknown,
+}
+
+/// Where the core configuration was loaded from
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    Default,
+    Discovered,
+    Storage,
+    Yaml,
+
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Deserialize, Debug)]
@@ -40,6 +93,53 @@ pub struct ConfigResponse {
     pub unit_system: UnitSystemConfig,
     pub version: String,
     pub whitelist_external_dirs: Vec<String>,
+
+    #[serde(default)]
+    pub allowlist_external_dirs: Vec<String>,
+
+    #[serde(default)]
+    pub allowlist_external_urls: Vec<String>,
+
+    #[serde(default)]
+    pub config_source: Option<ConfigSource>,
+
+    /// ISO 3166-1 alpha-2 country code
+    #[serde(default)]
+    pub country: Option<String>,
+
+    /// ISO 4217 currency code
+    #[serde(default)]
+    pub currency: Option<String>,
+
+    #[serde(default)]
+    pub debug: Option<bool>,
+
+    #[serde(default)]
+    pub external_url: Option<String>,
+
+    #[serde(default)]
+    pub internal_url: Option<String>,
+
+    /// BCP 47 language tag
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Radius of the home zone in meters
+    #[serde(default)]
+    pub radius: Option<u32>,
+
+    #[serde(default)]
+    pub recovery_mode: Option<bool>,
+
+    #[serde(default)]
+    pub safe_mode: Option<bool>,
+
+    #[serde(default)]
+    pub state: Option<CoreState>,
+
+    /// Fields that are not modeled above, such as those added by newer versions of Home Assistant
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
 pub type EventsResponse = Vec<EventEntry>;
@@ -55,7 +155,10 @@ pub type ServicesResponse = Vec<ServiceEntry>;
 #[derive(Deserialize, Debug)]
 pub struct ServiceEntry {
     pub domain: String,
-    pub services: Vec<String>,
+
+    /// Schema of every service of the domain, keyed by service name
+    #[serde(deserialize_with = "service_schema::services")]
+    pub services: HashMap<String, ServiceSchema>,
 }
 
 #[derive(Default)]
@@ -101,30 +204,49 @@ impl Parameters for HistoryParams {
     }
 }
 
-pub type HistoryResponse = Vec<Vec<HistoryEntry>>;
-
-#[derive(Deserialize, Debug)]
-pub struct HistoryEntry {
-    #[serde(default)]
-    pub attributes: Option<HashMap<String, serde_json::Value>>,
+/// Controls how [`Client::get_history_chunked`](crate::Client::get_history_chunked) and
+/// [`Client::get_logbook_chunked`](crate::Client::get_logbook_chunked) split a long period
+#[derive(Debug, Clone)]
+pub struct ChunkOptions {
+    /// Length of the period fetched by a single request
+    pub window: TimeDelta,
 
-    #[serde(default)]
-    pub entity_id: Option<String>,
+    /// Maximum number of requests in flight at once
+    pub concurrency: usize,
+}
 
-    #[serde(default)]
-    pub last_changed: Option<DateTime<FixedOffset>>,
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            window: TimeDelta::days(1),
+            concurrency: 4,
+        }
+    }
+}
 
-    #[serde(default)]
-    pub last_updated: Option<DateTime<FixedOffset>>,
+pub type HistoryResponse = Vec<Vec<EntityState>>;
 
-    pub state: Option<StateEnum>,
-}
+pub type HistorySeriesResponse = Vec<HistorySeries>;
 
 #[derive(Default)]
 pub struct LogbookParams {
-    pub entity: Option<String>,
+    /// Only return entries of these entities. All entities are included when empty.
+    pub entity_ids: Vec<String>,
+
+    /// Only return entries caused by this context. Home Assistant rejects combining it with
+    /// `entity_ids`.
+    pub context_id: Option<String>,
+
+    /// Timestamp the period is based on, defaults to the start of the current day in the time
+    /// zone of Home Assistant. The period begins at this timestamp when `end_time` is set.
     pub start_time: Option<DateTime<FixedOffset>>,
+
+    /// End of the period, takes precedence over `period_days`
     pub end_time: Option<DateTime<FixedOffset>>,
+
+    /// Length of the period in days, defaults to 1. Without `end_time` the period ends one day
+    /// after `start_time`, so it begins `period_days - 1` days before `start_time`.
+    pub period_days: Option<u32>,
 }
 
 impl Parameters for LogbookParams {
@@ -136,14 +258,22 @@ impl Parameters for LogbookParams {
             endpoint.push_str(format!("/{}", start_time.to_rfc3339()).as_str());
         }
 
-        if let Some(ref entity) = self.entity {
-            query.push(("entity".to_owned(), entity.to_owned()));
+        if !self.entity_ids.is_empty() {
+            query.push(("entity".to_owned(), self.entity_ids.join(",")));
+        }
+
+        if let Some(context_id) = self.context_id {
+            query.push(("context_id".to_owned(), context_id));
         }
 
         if let Some(ref end_time) = self.end_time {
             query.push(("end_time".to_owned(), end_time.to_rfc3339()));
         }
 
+        if let Some(period_days) = self.period_days {
+            query.push(("period".to_owned(), period_days.to_string()));
+        }
+
         Request { endpoint, query }
     }
 }
@@ -165,30 +295,242 @@ pub struct LogbookEntry {
 
     #[serde(default)]
     pub when: Option<DateTime<FixedOffset>>,
+
+    #[serde(default)]
+    pub state: Option<StateEnum>,
+
+    #[serde(default)]
+    pub icon: Option<String>,
+
+    /// What triggered the entry, such as the trigger of an automation
+    #[serde(default)]
+    pub source: Option<String>,
+
+    #[serde(default)]
+    pub context_id: Option<String>,
+
+    /// User that caused the entry, if it was caused by a user
+    #[serde(default)]
+    pub context_user_id: Option<String>,
+
+    /// Type of the event that caused the entry, such as `call_service` or `automation_triggered`
+    #[serde(default)]
+    pub context_event_type: Option<String>,
+
+    /// Domain of the service call that caused the entry
+    #[serde(default)]
+    pub context_domain: Option<String>,
+
+    /// Service call that caused the entry
+    #[serde(default)]
+    pub context_service: Option<String>,
+
+    /// Entity that caused the entry, such as an automation or script
+    #[serde(default)]
+    pub context_entity_id: Option<String>,
+
+    #[serde(default)]
+    pub context_entity_id_name: Option<String>,
+
+    #[serde(default)]
+    pub context_name: Option<String>,
+
+    #[serde(default)]
+    pub context_message: Option<String>,
+
+    #[serde(default)]
+    pub context_source: Option<String>,
+
+    #[serde(default)]
+    pub context_state: Option<StateEnum>,
 }
 
-pub type StatesResponse = Vec<StateEntry>;
+pub type StatesResponse = Vec<EntityState>;
 
-#[derive(Deserialize, Debug)]
-pub struct StateEntry {
-    pub attributes: HashMap<String, serde_json::Value>,
+/// Severity of an [`ErrorLogEntry`], ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl LogLevel {
+    pub(crate) fn parse(level: &str) -> Option<Self> {
+        match level {
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARNING" | "WARN" => Some(LogLevel::Warning),
+            "ERROR" => Some(LogLevel::Error),
+            "CRITICAL" | "FATAL" => Some(LogLevel::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// A single record of the Home Assistant log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLogEntry {
+    /// Time the record was logged, in the local time zone of the Home Assistant instance
+    pub timestamp: NaiveDateTime,
+
+    /// `None` for log formats that do not include the level
+    pub level: Option<LogLevel>,
+
+    /// Thread that logged the record, such as `MainThread`
+    pub thread: Option<String>,
+
+    /// Name of the logger, such as `homeassistant.components.recorder`
+    pub logger: String,
+
+    /// Integration derived from the logger name, such as `recorder`
+    pub integration: Option<String>,
+
+    /// First line of the message
+    pub message: String,
+
+    /// Lines following the first line of the message, usually a Python traceback
+    pub traceback: Option<String>,
+}
+
+/// Parsed Home Assistant log as returned by [`Client::get_error_log_entries`](crate::Client::get_error_log_entries)
+#[derive(Debug, Clone, Default)]
+pub struct ErrorLog {
+    pub entries: Vec<ErrorLogEntry>,
+}
+
+impl ErrorLog {
+    /// Splits the plaintext log into records
+    ///
+    /// Lines that do not start a new record are attached to the traceback of the previous record.
+    /// Lines before the first record, which appear when the log has been truncated, are dropped.
+    pub fn parse(log: &str) -> Self {
+        ErrorLog {
+            entries: error_log::parse(log),
+        }
+    }
+
+    /// Returns the entries that match every criteria of `filter`
+    pub fn filter(&self, filter: ErrorLogFilter) -> impl Iterator<Item = &ErrorLogEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| filter.matches(entry))
+    }
+}
+
+/// Criteria used to select [`ErrorLogEntry`] records, unset criteria match every entry
+#[derive(Debug, Clone, Default)]
+pub struct ErrorLogFilter {
+    /// Only keep entries at or above this level. Entries without a level never match.
+    pub min_level: Option<LogLevel>,
+
+    /// Only keep entries of this integration, matched against the integration or the full logger name
+    pub integration: Option<String>,
+
+    /// Only keep entries logged at or after this time
+    pub since: Option<NaiveDateTime>,
+
+    /// Only keep entries logged before this time
+    pub until: Option<NaiveDateTime>,
+}
+
+impl ErrorLogFilter {
+    pub fn matches(&self, entry: &ErrorLogEntry) -> bool {
+        if let Some(min_level) = self.min_level {
+            match entry.level {
+                Some(level) if level >= min_level => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(ref integration) = self.integration {
+            if entry.integration.as_ref() != Some(integration) && entry.logger != *integration {
+                return false;
+            }
+        }
+
+        if self.since.is_some_and(|since| entry.timestamp < since) {
+            return false;
+        }
+
+        if self.until.is_some_and(|until| entry.timestamp >= until) {
+            return false;
+        }
+
+        true
+    }
+}
+
+pub struct CameraProxyParams {
     pub entity_id: String,
 
-    pub last_changed: DateTime<FixedOffset>,
+    /// Appended as the `time` query parameter to bypass caches between the client and Home
+    /// Assistant
+    pub timestamp: Option<DateTime<FixedOffset>>,
+}
 
-    pub state: Option<StateEnum>,
+impl Parameters for CameraProxyParams {
+    fn into_request(self) -> Request {
+        let mut query = Vec::new();
+        let endpoint = format!("/api/camera_proxy/{}", &self.entity_id);
+
+        if let Some(timestamp) = self.timestamp {
+            query.push(("time".to_owned(), timestamp.timestamp_millis().to_string()));
+        }
+
+        Request { endpoint, query }
+    }
 }
 
-#[derive(Deserialize, Debug)]
-pub struct StatesEntityResponse {
-    pub attributes: HashMap<String, serde_json::Value>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageDimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug)]
+pub struct CameraProxyResponse {
+    /// Value of the `Content-Type` header, for example `image/jpeg`
+    pub content_type: Option<String>,
+
+    /// Dimensions read from the image header for JPEG, PNG and GIF images
+    pub dimensions: Option<ImageDimensions>,
+
+    pub image: Bytes,
+}
+
+pub struct CameraStreamParams {
     pub entity_id: String,
 
-    pub last_changed: DateTime<FixedOffset>,
+    /// Minimum time between two frames yielded by the stream. Frames received sooner are dropped,
+    /// which throttles the frame rate, for example `Duration::from_millis(200)` for at most 5 frames
+    /// per second.
+    pub min_frame_interval: Option<Duration>,
+}
 
-    pub last_updated: DateTime<FixedOffset>,
+impl Parameters for CameraStreamParams {
+    fn into_request(self) -> Request {
+        Request {
+            endpoint: format!("/api/camera_proxy_stream/{}", &self.entity_id),
+            query: Vec::new(),
+        }
+    }
+}
 
-    pub state: Option<StateEnum>,
+#[derive(Debug)]
+pub struct CameraFrame {
+    /// Time at which the frame was received
+    pub timestamp: DateTime<FixedOffset>,
+
+    /// Value of the `Content-Type` header of the frame, usually `image/jpeg`
+    pub content_type: Option<String>,
+
+    /// Dimensions read from the image header for JPEG, PNG and GIF images
+    pub dimensions: Option<ImageDimensions>,
+
+    pub image: Bytes,
 }
 
 #[derive(Default)]
@@ -0,0 +1,424 @@
+//! Compact decoding of `/api/history/period` responses and per-entity history lookups
+
+use crate::{EntityState, StateEnum};
+
+use std::{collections::BTreeMap, fmt};
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// State changes of a single entity, ordered as returned by Home Assistant
+///
+/// Decoded directly from the response without building intermediate entries, only the entity id,
+/// the state and `last_changed` of each entry are kept. Attributes and any other fields are
+/// skipped. Entries with a `null` state are dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistorySeries {
+    pub entity_id: String,
+    pub points: Vec<(DateTime<FixedOffset>, StateEnum)>,
+}
+
+/// Period during which an entity kept the same state
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateInterval<'a> {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub state: &'a StateEnum,
+}
+
+impl HistorySeries {
+    /// Returns the state the entity was in at `instant`, or `None` if `instant` is before the
+    /// first state change
+    pub fn state_at(&self, instant: DateTime<FixedOffset>) -> Option<&StateEnum> {
+        let index = self.points.partition_point(|(time, _)| *time <= instant);
+
+        index.checked_sub(1).map(|index| &self.points[index].1)
+    }
+
+    /// Returns the periods between consecutive state changes
+    ///
+    /// The last state lasts until `end`, which usually is the end of the requested history period.
+    pub fn intervals(&self, end: DateTime<FixedOffset>) -> impl Iterator<Item = StateInterval<'_>> {
+        self.points
+            .iter()
+            .enumerate()
+            .map(move |(index, (start, state))| {
+                let interval_end = self
+                    .points
+                    .get(index + 1)
+                    .map_or(end, |(next, _)| *next)
+                    .max(*start);
+
+                StateInterval {
+                    start: *start,
+                    end: interval_end,
+                    state,
+                }
+            })
+    }
+
+    /// Samples the state every `step` from `start` until `end`, `end` excluded
+    ///
+    /// Returns no samples if `step` is not positive.
+    pub fn resample(
+        &self,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+        step: TimeDelta,
+    ) -> impl Iterator<Item = (DateTime<FixedOffset>, Option<&StateEnum>)> {
+        let step = (step > TimeDelta::zero()).then_some(step);
+
+        std::iter::successors(step.map(|_| start), move |time| {
+            step.and_then(|step| time.checked_add_signed(step))
+        })
+        .take_while(move |time| *time < end)
+        .map(|time| (time, self.state_at(time)))
+    }
+
+    /// Removes the points that repeat the state of the previous point
+    pub fn merge_adjacent(&mut self) {
+        self.points
+            .dedup_by(|(_, state), (_, previous)| state == previous);
+    }
+}
+
+/// History of several entities keyed by entity id
+///
+/// Built from either [`HistoryResponse`](crate::get::HistoryResponse) or
+/// [`HistorySeriesResponse`](crate::get::HistorySeriesResponse). Series of the same entity are
+/// concatenated and the points of every series are sorted by time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct History {
+    series: BTreeMap<String, HistorySeries>,
+}
+
+impl History {
+    pub fn get(&self, entity_id: &str) -> Option<&HistorySeries> {
+        self.series.get(entity_id)
+    }
+
+    pub fn entity_ids(&self) -> impl Iterator<Item = &str> {
+        self.series.keys().map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HistorySeries> {
+        self.series.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    /// Returns the state `entity_id` was in at `instant`
+    pub fn state_at(&self, entity_id: &str, instant: DateTime<FixedOffset>) -> Option<&StateEnum> {
+        self.get(entity_id)?.state_at(instant)
+    }
+
+    /// Removes the points that repeat the state of the previous point in every series
+    pub fn merge_adjacent(&mut self) {
+        self.series
+            .values_mut()
+            .for_each(HistorySeries::merge_adjacent);
+    }
+
+    /// Adds the points of `series` to the history
+    pub fn insert(&mut self, series: HistorySeries) {
+        let existing = self
+            .series
+            .entry(series.entity_id)
+            .or_insert_with_key(|entity_id| HistorySeries {
+                entity_id: entity_id.clone(),
+                points: Vec::new(),
+            });
+
+        existing.points.extend(series.points);
+        existing.points.sort_by_key(|(time, _)| *time);
+    }
+
+    /// Appends the series of the window following the ones already in the history
+    ///
+    /// Points not later than the last known point are dropped, as well as a first point repeating
+    /// the last known state, which Home Assistant reports at the start of every window.
+    pub(crate) fn append_window(&mut self, series: HistorySeries) {
+        let existing = self
+            .series
+            .entry(series.entity_id)
+            .or_insert_with_key(|entity_id| HistorySeries {
+                entity_id: entity_id.clone(),
+                points: Vec::new(),
+            });
+
+        let last_time = existing.points.last().map(|(time, _)| *time);
+        let mut points = series
+            .points
+            .into_iter()
+            .skip_while(|(time, _)| last_time.is_some_and(|last_time| *time <= last_time))
+            .peekable();
+
+        if let (Some((_, last)), Some((_, first))) = (existing.points.last(), points.peek()) {
+            if last == first {
+                points.next();
+            }
+        }
+
+        existing.points.extend(points);
+    }
+}
+
+impl FromIterator<HistorySeries> for History {
+    fn from_iter<I: IntoIterator<Item = HistorySeries>>(iter: I) -> Self {
+        let mut history = History::default();
+        iter.into_iter().for_each(|series| history.insert(series));
+
+        history
+    }
+}
+
+impl From<Vec<HistorySeries>> for History {
+    fn from(series: Vec<HistorySeries>) -> Self {
+        series.into_iter().collect()
+    }
+}
+
+impl From<Vec<Vec<EntityState>>> for History {
+    fn from(history: Vec<Vec<EntityState>>) -> Self {
+        history
+            .into_iter()
+            .filter_map(|entries| {
+                let entity_id = entries.first()?.entity_id.clone();
+                let points = entries
+                    .into_iter()
+                    .filter_map(|entry| Some((entry.last_changed, entry.state?)))
+                    .collect();
+
+                Some(HistorySeries { entity_id, points })
+            })
+            .collect()
+    }
+}
+
+impl IntoIterator for History {
+    type Item = HistorySeries;
+    type IntoIter = std::collections::btree_map::IntoValues<String, HistorySeries>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.series.into_values()
+    }
+}
+
+/// Single entry of the history, the entity id is only present in the first entry of a minimal
+/// response
+#[derive(Deserialize)]
+struct Entry {
+    #[serde(default)]
+    entity_id: Option<String>,
+
+    state: Option<StateEnum>,
+
+    last_changed: DateTime<FixedOffset>,
+}
+
+struct HistorySeriesVisitor;
+
+impl<'de> Visitor<'de> for HistorySeriesVisitor {
+    type Value = HistorySeries;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a list of state changes of one entity")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entity_id = None;
+        let mut points = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(entry) = seq.next_element::<Entry>()? {
+            if entity_id.is_none() {
+                entity_id = entry.entity_id;
+            }
+
+            if let Some(state) = entry.state {
+                points.push((entry.last_changed, state));
+            }
+        }
+
+        Ok(HistorySeries {
+            entity_id: entity_id
+                .ok_or_else(|| de::Error::custom("history does not contain an entity_id"))?,
+            points,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for HistorySeries {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(HistorySeriesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_minimal_response() {
+        let series: Vec<HistorySeries> = serde_json::from_value(json!([
+            [
+                {
+                    "entity_id": "sensor.temperature",
+                    "state": "21.5",
+                    "attributes": {"unit_of_measurement": "°C"},
+                    "last_changed": "2024-05-01T12:00:00+00:00",
+                    "last_updated": "2024-05-01T12:00:00+00:00"
+                },
+                {"state": "22", "last_changed": "2024-05-01T13:00:00+00:00"},
+                {"state": null, "last_changed": "2024-05-01T13:30:00+00:00"},
+                {"state": "unavailable", "last_changed": "2024-05-01T14:00:00+00:00"}
+            ],
+            [
+                {
+                    "entity_id": "light.kitchen",
+                    "state": "on",
+                    "last_changed": "2024-05-01T12:00:00+00:00"
+                }
+            ]
+        ]))
+        .unwrap();
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].entity_id, "sensor.temperature");
+        assert_eq!(
+            series[0]
+                .points
+                .iter()
+                .map(|(_, state)| state.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                StateEnum::Decimal(21.5),
+                StateEnum::Integer(22),
+                StateEnum::String("unavailable".to_owned())
+            ]
+        );
+        assert_eq!(
+            series[0].points[1].0,
+            DateTime::parse_from_rfc3339("2024-05-01T13:00:00+00:00").unwrap()
+        );
+        assert_eq!(series[1].entity_id, "light.kitchen");
+    }
+
+    #[test]
+    fn test_missing_entity_id() {
+        let series = serde_json::from_value::<Vec<HistorySeries>>(json!([[
+            {"state": "on", "last_changed": "2024-05-01T12:00:00+00:00"}
+        ]]));
+
+        assert!(series.is_err());
+    }
+
+    fn time(hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!("2024-05-01T{:02}:{:02}:00+00:00", hour, minute))
+            .unwrap()
+    }
+
+    fn light() -> HistorySeries {
+        HistorySeries {
+            entity_id: "light.kitchen".to_owned(),
+            points: vec![
+                (time(1, 0), StateEnum::String("off".to_owned())),
+                (time(2, 0), StateEnum::String("on".to_owned())),
+                (time(3, 0), StateEnum::String("on".to_owned())),
+                (time(4, 0), StateEnum::String("off".to_owned())),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_state_at() {
+        let series = light();
+
+        assert_eq!(series.state_at(time(0, 59)), None);
+        assert_eq!(
+            series.state_at(time(1, 0)),
+            Some(&StateEnum::String("off".to_owned()))
+        );
+        assert_eq!(
+            series.state_at(time(2, 30)),
+            Some(&StateEnum::String("on".to_owned()))
+        );
+        assert_eq!(
+            series.state_at(time(23, 0)),
+            Some(&StateEnum::String("off".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_intervals_and_merge() {
+        let mut series = light();
+        series.merge_adjacent();
+
+        let intervals = series.intervals(time(5, 0)).collect::<Vec<_>>();
+        assert_eq!(intervals.len(), 3);
+        assert_eq!(intervals[1].start, time(2, 0));
+        assert_eq!(intervals[1].end, time(4, 0));
+        assert_eq!(intervals[1].state, &StateEnum::String("on".to_owned()));
+        assert_eq!(intervals[2].end, time(5, 0));
+    }
+
+    #[test]
+    fn test_resample() {
+        let series = light();
+
+        let samples = series
+            .resample(time(0, 30), time(3, 0), TimeDelta::minutes(60))
+            .map(|(time, state)| (time, state.cloned()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            samples,
+            vec![
+                (time(0, 30), None),
+                (time(1, 30), Some(StateEnum::String("off".to_owned()))),
+                (time(2, 30), Some(StateEnum::String("on".to_owned()))),
+            ]
+        );
+
+        assert_eq!(
+            series
+                .resample(time(0, 0), time(3, 0), TimeDelta::zero())
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_history_insert_concatenates_series() {
+        let mut first = light();
+        let second = HistorySeries {
+            entity_id: "light.kitchen".to_owned(),
+            points: first.points.split_off(2),
+        };
+
+        let history: History = vec![second, first].into();
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.get("light.kitchen"), Some(&light()));
+        assert_eq!(
+            history.state_at("light.kitchen", time(3, 30)),
+            Some(&StateEnum::String("on".to_owned()))
+        );
+    }
+}
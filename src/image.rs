@@ -0,0 +1,137 @@
+//! Minimal image header parsing used to report the dimensions of camera images
+
+use crate::get::ImageDimensions;
+
+/// Reads the dimensions of a JPEG, PNG or GIF image from its header
+///
+/// Returns `None` if the format is not recognized or the header is truncated.
+pub(crate) fn dimensions(data: &[u8]) -> Option<ImageDimensions> {
+    if data.starts_with(&[0xff, 0xd8]) {
+        jpeg_dimensions(data)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_dimensions(data)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        gif_dimensions(data)
+    } else {
+        None
+    }
+}
+
+fn png_dimensions(data: &[u8]) -> Option<ImageDimensions> {
+    // The IHDR chunk always comes first, right after the 8 byte signature
+    if data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+
+    Some(ImageDimensions {
+        width: u32::from_be_bytes(data.get(16..20)?.try_into().ok()?),
+        height: u32::from_be_bytes(data.get(20..24)?.try_into().ok()?),
+    })
+}
+
+fn gif_dimensions(data: &[u8]) -> Option<ImageDimensions> {
+    Some(ImageDimensions {
+        width: u16::from_le_bytes(data.get(6..8)?.try_into().ok()?) as u32,
+        height: u16::from_le_bytes(data.get(8..10)?.try_into().ok()?) as u32,
+    })
+}
+
+fn jpeg_dimensions(data: &[u8]) -> Option<ImageDimensions> {
+    let mut offset = 2;
+
+    loop {
+        if *data.get(offset)? != 0xff {
+            return None;
+        }
+
+        let marker = *data.get(offset + 1)?;
+        match marker {
+            // Fill bytes preceding a marker
+            0xff => {
+                offset += 1;
+                continue;
+            }
+            // Markers without a payload
+            0x01 | 0xd0..=0xd9 => {
+                offset += 2;
+                continue;
+            }
+            _ => {}
+        }
+
+        let length = u16::from_be_bytes(data.get(offset + 2..offset + 4)?.try_into().ok()?);
+
+        // Start of frame markers, excluding DHT (0xc4), JPG (0xc8) and DAC (0xcc)
+        if matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+            return Some(ImageDimensions {
+                height: u16::from_be_bytes(data.get(offset + 5..offset + 7)?.try_into().ok()?)
+                    as u32,
+                width: u16::from_be_bytes(data.get(offset + 7..offset + 9)?.try_into().ok()?)
+                    as u32,
+            });
+        }
+
+        offset += 2 + length as usize;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const JPEG_HEADER: [u8; 31] = [
+        0xff, 0xd8, // SOI
+        0xff, 0xe0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x00, 0x00, 0x01, 0x00,
+        0x01, 0x00, 0x00, // APP0
+        0xff, 0xc0, 0x00, 0x11, 0x08, 0x00, 0xf0, 0x01, 0x40, 0x03, 0x01, // SOF0
+    ];
+
+    #[test]
+    fn test_jpeg() {
+        assert_eq!(
+            dimensions(&JPEG_HEADER),
+            Some(ImageDimensions {
+                width: 320,
+                height: 240
+            })
+        );
+    }
+
+    #[test]
+    fn test_jpeg_truncated() {
+        assert_eq!(dimensions(&JPEG_HEADER[..24]), None);
+    }
+
+    #[test]
+    fn test_png() {
+        let mut data = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        data.extend_from_slice(&640u32.to_be_bytes());
+        data.extend_from_slice(&480u32.to_be_bytes());
+
+        assert_eq!(
+            dimensions(&data),
+            Some(ImageDimensions {
+                width: 640,
+                height: 480
+            })
+        );
+    }
+
+    #[test]
+    fn test_gif() {
+        let data = b"GIF89a\x20\x03\x58\x02";
+
+        assert_eq!(
+            dimensions(data),
+            Some(ImageDimensions {
+                width: 800,
+                height: 600
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        assert_eq!(dimensions(b"<html></html>"), None);
+    }
+}
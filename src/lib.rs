@@ -38,11 +38,29 @@
 //! }
 //! ```
 
+pub mod analytics;
+mod camera_stream;
+mod chunked;
 mod client;
+mod config_check;
+mod conversation;
+mod entity_state;
+mod error_log;
 pub mod errors;
 pub mod get;
+mod history_series;
+mod image;
 pub mod post;
+pub mod raw;
+mod registry;
+mod service_schema;
 mod state_enum;
 
-pub use client::Client;
+pub use camera_stream::CameraStream;
+#[cfg(feature = "gzip")]
+pub use client::RequestCompression;
+pub use client::{Client, ClientOptions};
+pub use conversation::ConversationSession;
+pub use entity_state::{EntityState, StateContext};
+pub use registry::{Registry, RegistryResults};
 pub use state_enum::StateEnum;
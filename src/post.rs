@@ -1,9 +1,8 @@
-use crate::StateEnum;
+use crate::{config_check, errors, EntityState, StateEnum};
 
-use std::collections::HashMap;
+use std::fmt;
 
-use chrono::{DateTime, FixedOffset};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 pub struct Request<S: Serialize> {
     pub endpoint: String,
@@ -17,19 +16,24 @@ pub trait Requestable {
 }
 
 #[derive(Serialize, Debug)]
-pub struct StateRequestBody {
-    pub state: String,
-    pub attributes: HashMap<String, String>,
+pub struct StateRequestBody<A: Serialize> {
+    pub state: StateEnum,
+    pub attributes: A,
 }
 
-pub struct StateParams {
+/// Parameters of [`Client::post_states`](crate::Client::post_states)
+///
+/// The attributes can be any type that serializes to a JSON object, such as a
+/// [`serde_json::Map`], a `HashMap` or a struct deriving [`Serialize`]. The state accepts anything
+/// that converts into a [`StateEnum`], so `"on".into()`, `21.5.into()` and `true.into()` all work.
+pub struct StateParams<A: Serialize = serde_json::Map<String, serde_json::Value>> {
     pub entity_id: String,
-    pub state: String,
-    pub attributes: HashMap<String, String>,
+    pub state: StateEnum,
+    pub attributes: A,
 }
 
-impl Requestable for StateParams {
-    type S = StateRequestBody;
+impl<A: Serialize> Requestable for StateParams<A> {
+    type S = StateRequestBody<A>;
 
     fn into_request(self) -> Request<Self::S> {
         let body = StateRequestBody {
@@ -44,28 +48,13 @@ impl Requestable for StateParams {
     }
 }
 
-#[derive(Deserialize, Debug)]
-pub struct StateContextStateResponse {
-    pub id: String,
-    pub parent_id: Option<String>,
-    pub user_id: Option<String>,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct StateResponse {
-    pub entity_id: String,
-
-    pub state: Option<StateEnum>,
-
-    pub attributes: HashMap<String, serde_json::Value>,
-
-    pub last_changed: DateTime<FixedOffset>,
-
-    pub last_reported: DateTime<FixedOffset>,
+/// Response of [`Client::post_states`](crate::Client::post_states)
+#[derive(Debug)]
+pub struct StateUpdateResponse {
+    /// `true` if the entity did not exist and was created, `false` if an existing entity was updated
+    pub created: bool,
 
-    pub last_updated: DateTime<FixedOffset>,
-
-    pub context: StateContextStateResponse,
+    pub state: EntityState,
 }
 
 pub struct EventParams {
@@ -89,13 +78,87 @@ pub struct EventResponse {
     pub message: String,
 }
 
+/// Entities, devices, areas, floors and labels targeted by a service call
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ServiceTarget {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub entity_id: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub device_id: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub area_id: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub floor_id: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub label_id: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ServiceRequestBody {
+    #[serde(flatten)]
+    pub target: ServiceTarget,
+
+    #[serde(flatten)]
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Default)]
+pub struct ServiceParams {
+    pub domain: String,
+    pub service: String,
+    pub target: ServiceTarget,
+
+    /// Service data such as `brightness` for `light.turn_on`
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Requestable for ServiceParams {
+    type S = ServiceRequestBody;
+
+    fn into_request(self) -> Request<Self::S> {
+        let body = ServiceRequestBody {
+            target: self.target,
+            data: self.data,
+        };
+
+        Request {
+            endpoint: format!("/api/services/{}/{}", self.domain, self.service),
+            body,
+        }
+    }
+}
+
+/// States that changed while the service was being executed
+pub type ServiceResponse = Vec<EntityState>;
+
+/// Response of a service call made with `?return_response`
+///
+/// `T` is the type of the data returned by the service, for example a map of entity ids to
+/// forecasts for `weather.get_forecasts`. Use [`serde_json::Value`] to keep it untyped.
+#[derive(Deserialize, Debug)]
+pub struct ServiceCallResponse<T> {
+    pub changed_states: Vec<EntityState>,
+    pub service_response: T,
+}
+
+#[derive(Default)]
 pub struct TemplateParams {
     pub template: String,
+
+    /// Variables made available to the template in addition to Home Assistant's own
+    pub variables: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Serialize, Debug)]
 pub struct TemplateRequestBody {
     pub template: String,
+
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub variables: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Requestable for TemplateParams {
@@ -104,6 +167,7 @@ impl Requestable for TemplateParams {
     fn into_request(self) -> Request<Self::S> {
         let body = TemplateRequestBody {
             template: self.template,
+            variables: self.variables,
         };
 
         Request {
@@ -113,8 +177,277 @@ impl Requestable for TemplateParams {
     }
 }
 
+/// Parses the output of a rendered template into `T`
+///
+/// The output is first parsed as JSON, which covers numbers, lists and objects. Python's `True`,
+/// `False` and `None` literals, which Home Assistant renders for booleans and missing values, are
+/// recognized next. Anything else is treated as a plain string.
+pub(crate) fn parse_rendered_template<T: DeserializeOwned>(
+    rendered: &str,
+) -> Result<T, serde_json::Error> {
+    let rendered = rendered.trim();
+
+    let value = serde_json::from_str(rendered).unwrap_or_else(|_| match rendered {
+        "True" => serde_json::Value::Bool(true),
+        "False" => serde_json::Value::Bool(false),
+        "None" => serde_json::Value::Null,
+        _ => serde_json::Value::String(rendered.to_owned()),
+    });
+
+    match serde_json::from_value(value) {
+        // A string target should receive the output verbatim, even if it looked like JSON
+        Err(_) => serde_json::from_value(serde_json::Value::String(rendered.to_owned())),
+        result => result,
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckConfigResult {
+    Valid,
+    Invalid,
+
+    #[serde(other)]
+    Unknown,
+}
+
+/// A single error or warning reported by the configuration check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Message as reported by Home Assistant
+    pub message: String,
+
+    /// Integration the issue refers to, if it could be determined from the message
+    pub integration: Option<String>,
+
+    /// Configuration file that contains the issue, if Home Assistant reported it
+    pub file: Option<String>,
+
+    /// Line in [`ConfigIssue::file`] that contains the issue, if Home Assistant reported it
+    pub line: Option<u32>,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct CheckConfigResponse {
-    pub errors: Option<String>,
-    pub result: String,
+    pub result: CheckConfigResult,
+
+    #[serde(default, deserialize_with = "config_check::deserialize_issues")]
+    pub errors: Vec<ConfigIssue>,
+
+    #[serde(default, deserialize_with = "config_check::deserialize_issues")]
+    pub warnings: Vec<ConfigIssue>,
+}
+
+impl CheckConfigResponse {
+    pub fn is_valid(&self) -> bool {
+        self.result == CheckConfigResult::Valid
+    }
+
+    /// Turns an invalid configuration into [`errors::Error::InvalidConfig`] so that deployments can
+    /// be gated with `?`
+    ///
+    /// Returns the warnings of a valid configuration.
+    pub fn ensure_valid(self) -> Result<Vec<ConfigIssue>, errors::Error> {
+        if self.is_valid() {
+            Ok(self.warnings)
+        } else {
+            Err(errors::Error::InvalidConfig(self.errors))
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct IntentRequestBody {
+    pub name: String,
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Default)]
+pub struct IntentParams {
+    /// Name of the intent, for example `HassTurnOn`
+    pub name: String,
+
+    /// Slot values of the intent, for example `{"name": "kitchen light"}`
+    pub data: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Requestable for IntentParams {
+    type S = IntentRequestBody;
+
+    fn into_request(self) -> Request<Self::S> {
+        let body = IntentRequestBody {
+            name: self.name,
+            data: self.data,
+        };
+
+        Request {
+            endpoint: "/api/intent/handle".to_owned(),
+            body,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentResponseType {
+    ActionDone,
+    QueryAnswer,
+    Error,
+
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpeechEntry {
+    pub speech: String,
+
+    #[serde(default)]
+    pub extra_data: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct IntentSpeech {
+    #[serde(default)]
+    pub plain: Option<SpeechEntry>,
+
+    #[serde(default)]
+    pub ssml: Option<SpeechEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CardEntry {
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct IntentCard {
+    #[serde(default)]
+    pub simple: Option<CardEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentTargetType {
+    Area,
+    Domain,
+    DeviceClass,
+    Device,
+    Entity,
+    Custom,
+
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IntentTarget {
+    pub name: String,
+
+    #[serde(rename = "type")]
+    pub target_type: IntentTargetType,
+
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct IntentResponseData {
+    /// Targets the intent was matched against
+    #[serde(default)]
+    pub targets: Vec<IntentTarget>,
+
+    /// Targets the intent was successfully applied to
+    #[serde(default)]
+    pub success: Vec<IntentTarget>,
+
+    /// Targets the intent failed to apply to
+    #[serde(default)]
+    pub failed: Vec<IntentTarget>,
+
+    /// Error code when `response_type` is [`IntentResponseType::Error`], for example
+    /// `no_intent_match`
+    #[serde(default)]
+    pub code: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IntentResponse {
+    #[serde(default)]
+    pub speech: IntentSpeech,
+
+    #[serde(default)]
+    pub card: IntentCard,
+
+    #[serde(default)]
+    pub language: Option<String>,
+
+    pub response_type: IntentResponseType,
+
+    #[serde(default)]
+    pub data: IntentResponseData,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ConversationRequestBody {
+    pub text: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ConversationParams {
+    pub text: String,
+    pub language: Option<String>,
+
+    /// Conversation agent to process the text with. Home Assistant's default agent is used when
+    /// `None`.
+    pub agent_id: Option<String>,
+
+    /// Id returned by a previous turn to continue that conversation
+    pub conversation_id: Option<String>,
+}
+
+impl Requestable for ConversationParams {
+    type S = ConversationRequestBody;
+
+    fn into_request(self) -> Request<Self::S> {
+        let body = ConversationRequestBody {
+            text: self.text,
+            language: self.language,
+            agent_id: self.agent_id,
+            conversation_id: self.conversation_id,
+        };
+
+        Request {
+            endpoint: "/api/conversation/process".to_owned(),
+            body,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ConversationResponse {
+    pub response: IntentResponse,
+
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+
+    /// Whether the agent expects the user to reply, for example after asking a question
+    #[serde(default)]
+    pub continue_conversation: bool,
 }
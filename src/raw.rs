@@ -0,0 +1,122 @@
+//! Requests to arbitrary Home Assistant endpoints
+//!
+//! Integrations and custom components add REST endpoints that this crate does not model, such as
+//! `/api/shopping_list`. [`RawRequest`] sends requests to those endpoints while still applying
+//! authentication, base path handling, URL failover and error mapping.
+//!
+//! ```rust,no_run
+//! use home_assistant_rest::Client;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct ShoppingListItem {
+//!     name: String,
+//!     complete: bool,
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Client::new("http://localhost:8123", "TOKEN")?;
+//!
+//! let items: Vec<ShoppingListItem> = client.raw_get("/api/shopping_list").json().await?;
+//!
+//! let added: ShoppingListItem = client
+//!     .raw_post("/api/shopping_list/item")
+//!     .body(&serde_json::json!({"name": "Milk"}))
+//!     .json()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    client::{endpoint_url, error_for_status},
+    errors, Client,
+};
+
+use bytes::Bytes;
+use reqwest::Method;
+use serde::{de::DeserializeOwned, Serialize};
+
+type Result<T> = std::result::Result<T, errors::Error>;
+
+/// A request to an arbitrary endpoint, created by [`Client::raw_get`], [`Client::raw_post`] or
+/// [`Client::raw_delete`]
+///
+/// The request is sent by one of [`json`](RawRequest::json), [`bytes`](RawRequest::bytes),
+/// [`text`](RawRequest::text) or [`send`](RawRequest::send), depending on how the response should
+/// be read. Responses with an error status code are returned as
+/// [`errors::Error::ApiErrorResponse`].
+#[derive(Debug)]
+pub struct RawRequest<'a> {
+    client: &'a Client,
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Option<std::result::Result<serde_json::Value, serde_json::Error>>,
+}
+
+impl<'a> RawRequest<'a> {
+    pub(crate) fn new(client: &'a Client, method: Method, path: &str) -> Self {
+        RawRequest {
+            client,
+            method,
+            path: path.to_owned(),
+            query: Vec::new(),
+            body: None,
+        }
+    }
+
+    /// Appends a query parameter to the request URL
+    pub fn query<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets the JSON body of the request
+    pub fn body<B: Serialize + ?Sized>(mut self, body: &B) -> Self {
+        self.body = Some(serde_json::to_value(body));
+        self
+    }
+
+    /// Sends the request and deserializes the JSON response into `T`
+    pub async fn json<T: DeserializeOwned>(self) -> Result<T> {
+        let bytes = self.bytes().await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Sends the request and returns the raw response body
+    pub async fn bytes(self) -> Result<Bytes> {
+        Ok(self.send().await?.bytes().await?)
+    }
+
+    /// Sends the request and returns the response body as text
+    pub async fn text(self) -> Result<String> {
+        Ok(self.send().await?.text().await?)
+    }
+
+    /// Sends the request and returns the response, leaving it to the caller to read the body
+    pub async fn send(self) -> Result<reqwest::Response> {
+        let body = self
+            .body
+            .transpose()
+            .map_err(errors::Error::SerializeFailed)?;
+
+        let response = self
+            .client
+            .send(|base| {
+                let mut url = endpoint_url(base, &self.path);
+                if !self.query.is_empty() {
+                    url.query_pairs_mut().extend_pairs(&self.query);
+                }
+
+                let builder = self.client.build_request(self.method.clone(), url);
+                match body {
+                    Some(ref body) => self.client.with_json_body(builder, body),
+                    None => builder,
+                }
+            })
+            .await?;
+
+        error_for_status(response).await
+    }
+}
@@ -0,0 +1,205 @@
+//! Registry of named clients for managing many Home Assistant instances
+
+use crate::{errors, Client};
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::Future,
+};
+
+use futures_util::future::join_all;
+
+type Result<T> = std::result::Result<T, errors::Error>;
+
+/// Holds many named [`Client`]s and runs the same operation across them concurrently
+///
+/// ```rust,no_run
+/// use home_assistant_rest::{Client, Registry};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut registry = Registry::new();
+/// registry.insert("office", Client::new("http://office.local:8123", "TOKEN")?);
+/// registry.insert("warehouse", Client::new("http://warehouse.local:8123", "TOKEN")?);
+///
+/// let statuses = registry
+///     .run_all(|client| async move { client.get_api_status().await })
+///     .await;
+///
+/// for (name, error) in statuses.failures() {
+///     println!("{} is unreachable: {}", name, error);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    clients: BTreeMap<String, Client>,
+}
+
+impl Registry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `client` under `name`, returning the client previously registered under that name
+    pub fn insert<N: Into<String>>(&mut self, name: N, client: Client) -> Option<Client> {
+        self.clients.insert(name.into(), client)
+    }
+
+    /// Removes the client registered under `name`
+    pub fn remove(&mut self, name: &str) -> Option<Client> {
+        self.clients.remove(name)
+    }
+
+    /// Returns the client registered under `name`
+    pub fn get(&self, name: &str) -> Option<&Client> {
+        self.clients.get(name)
+    }
+
+    /// Returns the names of all registered instances in sorted order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    /// Returns the number of registered instances
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Returns `true` if no instance is registered
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Runs `operation` against every registered instance concurrently
+    pub async fn run_all<F, Fut, T>(&self, operation: F) -> RegistryResults<T>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.run(self.clients.keys().cloned(), operation).await
+    }
+
+    /// Runs `operation` concurrently against the instances listed in `names`
+    ///
+    /// Names that are not registered are reported as [`errors::Error::UnknownInstance`] failures.
+    /// The operation runs once per instance even if its name is listed several times.
+    pub async fn run_selected<I, N, F, Fut, T>(&self, names: I, operation: F) -> RegistryResults<T>
+    where
+        I: IntoIterator<Item = N>,
+        N: Into<String>,
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let names = names
+            .into_iter()
+            .map(Into::into)
+            .collect::<BTreeSet<String>>();
+
+        self.run(names.into_iter(), operation).await
+    }
+
+    async fn run<I, F, Fut, T>(&self, names: I, operation: F) -> RegistryResults<T>
+    where
+        I: Iterator<Item = String>,
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let operation = &operation;
+
+        let results = join_all(names.map(|name| async move {
+            let result = match self.clients.get(&name) {
+                Some(client) => operation(client.clone()).await,
+                None => Err(errors::Error::UnknownInstance(name.clone())),
+            };
+
+            (name, result)
+        }))
+        .await;
+
+        RegistryResults {
+            results: results.into_iter().collect(),
+        }
+    }
+}
+
+/// Per-instance results of an operation run across a [`Registry`]
+#[derive(Debug)]
+pub struct RegistryResults<T> {
+    results: BTreeMap<String, Result<T>>,
+}
+
+impl<T> RegistryResults<T> {
+    /// Returns the result for the instance registered under `name`
+    pub fn get(&self, name: &str) -> Option<&Result<T>> {
+        self.results.get(name)
+    }
+
+    /// Iterates over the results of every instance in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Result<T>)> {
+        self.results
+            .iter()
+            .map(|(name, result)| (name.as_str(), result))
+    }
+
+    /// Iterates over the instances for which the operation succeeded
+    pub fn successes(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.iter()
+            .filter_map(|(name, result)| result.as_ref().ok().map(|value| (name, value)))
+    }
+
+    /// Iterates over the instances for which the operation failed
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &errors::Error)> {
+        self.iter()
+            .filter_map(|(name, result)| result.as_ref().err().map(|error| (name, error)))
+    }
+
+    /// Returns `true` if the operation succeeded for every instance
+    pub fn all_succeeded(&self) -> bool {
+        self.results.values().all(|result| result.is_ok())
+    }
+
+    /// Returns the number of instances the operation was run against
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns `true` if the operation was not run against any instance
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Converts the results into the successful values keyed by instance name, or a
+    /// [`errors::RegistryError`] holding every failure if any instance failed
+    pub fn into_result(self) -> std::result::Result<BTreeMap<String, T>, errors::RegistryError> {
+        let mut successes = BTreeMap::new();
+        let mut failures = BTreeMap::new();
+
+        for (name, result) in self.results {
+            match result {
+                Ok(value) => {
+                    successes.insert(name, value);
+                }
+                Err(error) => {
+                    failures.insert(name, error);
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(successes)
+        } else {
+            Err(errors::RegistryError { failures })
+        }
+    }
+}
+
+impl<T> IntoIterator for RegistryResults<T> {
+    type Item = (String, Result<T>);
+    type IntoIter = std::collections::btree_map::IntoIter<String, Result<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
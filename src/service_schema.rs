@@ -0,0 +1,458 @@
+//! Schema of the services returned by `/api/services`
+
+use std::collections::HashMap;
+
+use serde::{de, Deserialize, Deserializer};
+
+/// Description of a single service, as defined in the `services.yaml` of its integration
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ServiceSchema {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Fields accepted in the service data, keyed by field name
+    #[serde(default)]
+    pub fields: HashMap<String, ServiceFieldEntry>,
+
+    /// Entities, devices and areas the service can target. `None` if the service takes no target.
+    #[serde(default)]
+    pub target: Option<TargetSelector>,
+
+    #[serde(default)]
+    pub response: Option<ServiceResponseSchema>,
+}
+
+impl ServiceSchema {
+    /// Returns every field of the service, including the fields nested in sections
+    pub fn all_fields(&self) -> impl Iterator<Item = (&String, &ServiceField)> {
+        self.fields.iter().flat_map(|(name, entry)| {
+            let fields: Box<dyn Iterator<Item = (&String, &ServiceField)>> = match entry {
+                ServiceFieldEntry::Field(field) => Box::new(std::iter::once((name, field))),
+                ServiceFieldEntry::Section(section) => Box::new(section.fields.iter()),
+            };
+            fields
+        })
+    }
+
+    /// Whether the service can or must be called with
+    /// [`Client::post_service_with_response`](crate::Client::post_service_with_response)
+    pub fn response_support(&self) -> ResponseSupport {
+        match self.response {
+            None => ResponseSupport::None,
+            Some(ServiceResponseSchema { optional: true }) => ResponseSupport::Optional,
+            Some(ServiceResponseSchema { optional: false }) => ResponseSupport::Only,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceResponseSchema {
+    /// `true` if the service can be called with or without returning a response
+    #[serde(default)]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseSupport {
+    /// The service never returns a response
+    None,
+
+    /// The service returns a response when asked to
+    Optional,
+
+    /// The service must be called with `return_response`
+    Only,
+}
+
+/// Entry of [`ServiceSchema::fields`], either a field or a section grouping several fields
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ServiceFieldEntry {
+    Section(ServiceSection),
+    Field(ServiceField),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServiceSection {
+    /// Whether the section is collapsed by default in the UI
+    #[serde(default)]
+    pub collapsed: bool,
+
+    pub fields: HashMap<String, ServiceField>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ServiceField {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub required: bool,
+
+    /// Whether the field is only shown in advanced mode
+    #[serde(default)]
+    pub advanced: bool,
+
+    #[serde(default)]
+    pub example: Option<serde_json::Value>,
+
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+
+    #[serde(default)]
+    pub selector: Option<Selector>,
+
+    /// Restricts the field to targets with the given features or attributes
+    #[serde(default)]
+    pub filter: Option<serde_json::Value>,
+}
+
+/// Entities, devices and areas a service can target
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TargetSelector {
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub entity: Vec<EntityFilter>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub device: Vec<DeviceFilter>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct EntityFilter {
+    #[serde(default)]
+    pub integration: Option<String>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub domain: Vec<String>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub device_class: Vec<String>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub supported_features: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DeviceFilter {
+    #[serde(default)]
+    pub integration: Option<String>,
+
+    #[serde(default)]
+    pub manufacturer: Option<String>,
+
+    #[serde(default)]
+    pub model: Option<String>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub entity: Vec<EntityFilter>,
+}
+
+/// Selector describing the kind of value a field accepts
+///
+/// The selectors commonly used by services are modeled, the others are kept as
+/// [`Selector::Other`] with their raw configuration.
+#[derive(Debug, Clone)]
+pub enum Selector {
+    Boolean,
+    Number(NumberSelector),
+    Text(TextSelector),
+    Select(SelectSelector),
+    Entity(EntitySelector),
+    Device(DeviceSelector),
+    Area(AreaSelector),
+    Target(TargetSelector),
+    Duration(DurationSelector),
+    Time,
+    Date,
+    Datetime,
+    ColorRgb,
+    ColorTemp(ColorTempSelector),
+    Object,
+    Template,
+    Other {
+        kind: String,
+        config: serde_json::Value,
+    },
+}
+
+impl<'de> Deserialize<'de> for Selector {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let selector = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+
+        let mut entries = selector.into_iter();
+        let (kind, config) = match (entries.next(), entries.next()) {
+            (Some(entry), None) => entry,
+            _ => {
+                return Err(de::Error::custom(
+                    "expected a selector with exactly one type",
+                ))
+            }
+        };
+
+        // Selectors without options are written as `boolean:` which Home Assistant returns as
+        // `null` rather than an empty object
+        let config = match config {
+            serde_json::Value::Null => serde_json::Value::Object(serde_json::Map::new()),
+            config => config,
+        };
+
+        fn typed<T: de::DeserializeOwned, E: de::Error>(config: serde_json::Value) -> Result<T, E> {
+            serde_json::from_value(config).map_err(de::Error::custom)
+        }
+
+        Ok(match kind.as_str() {
+            "boolean" => Selector::Boolean,
+            "number" => Selector::Number(typed(config)?),
+            "text" => Selector::Text(typed(config)?),
+            "select" => Selector::Select(typed(config)?),
+            "entity" => Selector::Entity(typed(config)?),
+            "device" => Selector::Device(typed(config)?),
+            "area" => Selector::Area(typed(config)?),
+            "target" => Selector::Target(typed(config)?),
+            "duration" => Selector::Duration(typed(config)?),
+            "time" => Selector::Time,
+            "date" => Selector::Date,
+            "datetime" => Selector::Datetime,
+            "color_rgb" => Selector::ColorRgb,
+            "color_temp" => Selector::ColorTemp(typed(config)?),
+            "object" => Selector::Object,
+            "template" => Selector::Template,
+            _ => Selector::Other { kind, config },
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberSelectorMode {
+    Box,
+    Slider,
+
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct NumberSelector {
+    #[serde(default)]
+    pub min: Option<f64>,
+
+    #[serde(default)]
+    pub max: Option<f64>,
+
+    /// `None` when the step is `any`
+    #[serde(default, deserialize_with = "step")]
+    pub step: Option<f64>,
+
+    #[serde(default)]
+    pub unit_of_measurement: Option<String>,
+
+    #[serde(default)]
+    pub mode: Option<NumberSelectorMode>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TextSelector {
+    #[serde(default)]
+    pub multiline: bool,
+
+    #[serde(default)]
+    pub multiple: bool,
+
+    /// HTML input type, such as `password` or `url`
+    #[serde(default, rename = "type")]
+    pub input_type: Option<String>,
+
+    #[serde(default)]
+    pub prefix: Option<String>,
+
+    #[serde(default)]
+    pub suffix: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SelectSelectorMode {
+    List,
+    Dropdown,
+
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SelectSelector {
+    #[serde(default)]
+    pub options: Vec<SelectOption>,
+
+    #[serde(default)]
+    pub multiple: bool,
+
+    /// Whether values other than the listed options are accepted
+    #[serde(default)]
+    pub custom_value: bool,
+
+    #[serde(default)]
+    pub mode: Option<SelectSelectorMode>,
+
+    #[serde(default)]
+    pub translation_key: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectOption {
+    pub value: String,
+    pub label: String,
+}
+
+impl<'de> Deserialize<'de> for SelectOption {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Options are either a plain value or a value with a label
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawOption {
+            Value(String),
+            Labeled { value: String, label: String },
+        }
+
+        Ok(match RawOption::deserialize(deserializer)? {
+            RawOption::Value(value) => SelectOption {
+                label: value.clone(),
+                value,
+            },
+            RawOption::Labeled { value, label } => SelectOption { value, label },
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct EntitySelector {
+    #[serde(default)]
+    pub multiple: bool,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub filter: Vec<EntityFilter>,
+
+    #[serde(default)]
+    pub integration: Option<String>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub domain: Vec<String>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub device_class: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DeviceSelector {
+    #[serde(default)]
+    pub multiple: bool,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub filter: Vec<DeviceFilter>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub entity: Vec<EntityFilter>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AreaSelector {
+    #[serde(default)]
+    pub multiple: bool,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub entity: Vec<EntityFilter>,
+
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub device: Vec<DeviceFilter>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DurationSelector {
+    #[serde(default)]
+    pub enable_day: bool,
+
+    #[serde(default)]
+    pub enable_millisecond: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ColorTempSelector {
+    #[serde(default)]
+    pub unit: Option<String>,
+
+    #[serde(default)]
+    pub min: Option<f64>,
+
+    #[serde(default)]
+    pub max: Option<f64>,
+
+    #[serde(default)]
+    pub min_mireds: Option<f64>,
+
+    #[serde(default)]
+    pub max_mireds: Option<f64>,
+}
+
+/// Accepts a single value or a list of values, as Home Assistant allows both in selectors
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+
+    Ok(match Option::<OneOrMany<T>>::deserialize(deserializer)? {
+        Some(OneOrMany::Many(values)) => values,
+        Some(OneOrMany::One(value)) => vec![value],
+        None => Vec::new(),
+    })
+}
+
+fn step<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let step = Option::<serde_json::Value>::deserialize(deserializer)?;
+
+    Ok(step.as_ref().and_then(serde_json::Value::as_f64))
+}
+
+/// Accepts the map of service name to schema returned by Home Assistant, as well as the list of
+/// service names shown in the API documentation
+pub(crate) fn services<'de, D>(deserializer: D) -> Result<HashMap<String, ServiceSchema>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Services {
+        Schemas(HashMap<String, ServiceSchema>),
+        Names(Vec<String>),
+    }
+
+    Ok(match Services::deserialize(deserializer)? {
+        Services::Schemas(services) => services,
+        Services::Names(names) => names
+            .into_iter()
+            .map(|name| (name, ServiceSchema::default()))
+            .collect(),
+    })
+}
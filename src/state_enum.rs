@@ -1,6 +1,6 @@
 use std::fmt;
 
-use serde::{de, Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Debug, Clone)]
 pub enum StateEnum {
@@ -24,6 +24,58 @@ impl std::cmp::PartialEq for StateEnum {
     }
 }
 
+impl StateEnum {
+    /// Returns the value of integer and decimal states
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            StateEnum::Integer(value) => Some(*value as f64),
+            StateEnum::Decimal(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for StateEnum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateEnum::Integer(value) => write!(f, "{}", value),
+            StateEnum::Decimal(value) => write!(f, "{}", value),
+            StateEnum::Boolean(value) => write!(f, "{}", value),
+            StateEnum::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<i64> for StateEnum {
+    fn from(value: i64) -> Self {
+        StateEnum::Integer(value)
+    }
+}
+
+impl From<f64> for StateEnum {
+    fn from(value: f64) -> Self {
+        StateEnum::Decimal(value)
+    }
+}
+
+impl From<bool> for StateEnum {
+    fn from(value: bool) -> Self {
+        StateEnum::Boolean(value)
+    }
+}
+
+impl From<String> for StateEnum {
+    fn from(value: String) -> Self {
+        StateEnum::String(value)
+    }
+}
+
+impl From<&str> for StateEnum {
+    fn from(value: &str) -> Self {
+        StateEnum::String(value.to_owned())
+    }
+}
+
 struct StateEnumVisitor;
 
 impl<'a> de::Visitor<'a> for StateEnumVisitor {
@@ -106,6 +158,16 @@ impl<'de> Deserialize<'de> for StateEnum {
     }
 }
 
+/// Home Assistant stores every state as a string, so the state is serialized in its string form
+impl Serialize for StateEnum {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use serde_json::json;
@@ -207,4 +269,24 @@ mod test {
         let value: Result<StateEnum, _> = serde_json::from_value(json!({"Hello": "World"}));
         assert!(value.is_err());
     }
+
+    #[test]
+    fn test_serialize_as_string() {
+        assert_eq!(
+            serde_json::to_value(StateEnum::from(128)).unwrap(),
+            json!("128")
+        );
+        assert_eq!(
+            serde_json::to_value(StateEnum::from(21.5)).unwrap(),
+            json!("21.5")
+        );
+        assert_eq!(
+            serde_json::to_value(StateEnum::from(true)).unwrap(),
+            json!("true")
+        );
+        assert_eq!(
+            serde_json::to_value(StateEnum::from("on")).unwrap(),
+            json!("on")
+        );
+    }
 }
@@ -0,0 +1,50 @@
+use home_assistant_rest::Client;
+
+fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+
+#[test]
+fn test_client_is_send_sync() {
+    assert_send_sync::<Client>();
+}
+
+#[test]
+fn test_debug_redacts_token() -> Result<(), Box<dyn std::error::Error>> {
+    let client = Client::new("http://localhost:8123", "super_secret_token")?;
+    let debug = format!("{:?}", client);
+
+    assert!(!debug.contains("super_secret_token"));
+    assert!(debug.contains("<redacted>"));
+    assert!(debug.contains("http://localhost:8123/"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cloned_client_shared_across_tasks_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = server
+        .mock("GET", "/api/")
+        .match_header("Authorization", "Bearer test_token")
+        .with_body(r#"{"message": "API running."}"#)
+        .expect(4)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let handles = (0..4)
+        .map(|_| {
+            let client = client.clone();
+            tokio::spawn(async move { client.get_api_status().await })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert_eq!(handle.await??.message, "API running.");
+    }
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
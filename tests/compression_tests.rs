@@ -0,0 +1,222 @@
+#![cfg(all(feature = "gzip", feature = "brotli", feature = "zstd"))]
+use std::{collections::HashMap, io::Read, io::Write};
+
+use home_assistant_rest::{post, Client, ClientOptions, RequestCompression, StateEnum};
+use mockito::{Matcher, Mock, ServerGuard};
+
+const HISTORY_BODY: &str = r#"
+[
+    [
+        {
+            "attributes": {
+                "friendly_name": "Weather Temperature",
+                "unit_of_measurement": "°C"
+            },
+            "entity_id": "sensor.weather_temperature",
+            "last_changed": "2016-02-06T22:15:00+00:00",
+            "last_updated": "2016-02-06T22:15:00+00:00",
+            "state": "-3.9"
+        }
+    ]
+]"#;
+
+const STATE_BODY: &str = r#"{
+    "entity_id":"sensor.big",
+    "state":"on",
+    "attributes":{},
+    "last_changed":"2023-04-25T23:49:34.728773+00:00",
+    "last_reported":"2024-04-25T23:49:34.728773+00:00",
+    "last_updated":"2023-04-25T23:49:34.728773+00:00",
+    "context":{
+        "id":"01GYXD54C8D0YFJ6ASFDGJBJR9",
+        "parent_id":null,
+        "user_id":null
+    }
+}"#;
+
+fn create_mock_server(server: &mut ServerGuard, method: &str, endpoint: &str) -> Mock {
+    server
+        .mock(method, endpoint)
+        .match_header("content-type", "application/json")
+        .match_header("Authorization", "Bearer test_token")
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    {
+        let mut encoder = brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+        encoder.write_all(data).unwrap();
+    }
+    output
+}
+
+fn zstd(data: &[u8]) -> Vec<u8> {
+    zstd::encode_all(data, 3).unwrap()
+}
+
+async fn assert_history_decoded(
+    encoding: &str,
+    body: Vec<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "GET", "/api/history/period")
+        .match_header("accept-encoding", Matcher::Regex(encoding.to_owned()))
+        .with_header("content-encoding", encoding)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let history = client.get_history(Default::default()).await?;
+
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0][0].state, Some(StateEnum::Decimal(-3.9)));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_gzip_response_async() -> Result<(), Box<dyn std::error::Error>> {
+    assert_history_decoded("gzip", gzip(HISTORY_BODY.as_bytes())).await
+}
+
+#[tokio::test]
+async fn test_brotli_response_async() -> Result<(), Box<dyn std::error::Error>> {
+    assert_history_decoded("br", brotli(HISTORY_BODY.as_bytes())).await
+}
+
+#[tokio::test]
+async fn test_zstd_response_async() -> Result<(), Box<dyn std::error::Error>> {
+    assert_history_decoded("zstd", zstd(HISTORY_BODY.as_bytes())).await
+}
+
+#[tokio::test]
+async fn test_gzip_text_response_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let log = "15-12-20 11:02:50 homeassistant.components.recorder: Found unfinished sessions";
+    let mock_server = create_mock_server(&mut server, "GET", "/api/error_log")
+        .with_header("content-encoding", "gzip")
+        .with_body(gzip(log.as_bytes()))
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    assert_eq!(client.get_error_log().await?, log);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+fn large_state_params() -> post::StateParams<HashMap<String, String>> {
+    let attributes = (0..100)
+        .map(|i| (format!("attribute_{}", i), format!("value_{}", i)))
+        .collect::<HashMap<_, _>>();
+
+    post::StateParams {
+        entity_id: "sensor.big".to_owned(),
+        state: "on".into(),
+        attributes,
+    }
+}
+
+#[tokio::test]
+async fn test_compressed_post_states_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "POST", "/api/states/sensor.big")
+        .match_header("content-encoding", "gzip")
+        .match_request(|request| {
+            let mut decoder = flate2::read::GzDecoder::new(request.body().unwrap().as_slice());
+            let mut body = String::new();
+            decoder.read_to_string(&mut body).unwrap();
+
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            body["state"] == "on" && body["attributes"]["attribute_42"] == "value_42"
+        })
+        .with_body(STATE_BODY)
+        .create_async()
+        .await;
+
+    let client = Client::with_options(
+        server.url().as_str(),
+        "test_token",
+        ClientOptions {
+            request_compression: Some(RequestCompression::default()),
+            ..ClientOptions::default()
+        },
+    )?;
+
+    let response = client.post_states(large_state_params()).await?;
+    assert_eq!(response.state.entity_id, "sensor.big");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_small_post_states_not_compressed_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "POST", "/api/states/sensor.big")
+        .match_header("content-encoding", Matcher::Missing)
+        .match_body(r#"{"state":"on","attributes":{}}"#)
+        .with_body(STATE_BODY)
+        .create_async()
+        .await;
+
+    let client = Client::with_options(
+        server.url().as_str(),
+        "test_token",
+        ClientOptions {
+            request_compression: Some(RequestCompression::default()),
+            ..ClientOptions::default()
+        },
+    )?;
+
+    let response = client
+        .post_states(post::StateParams {
+            entity_id: "sensor.big".to_owned(),
+            state: "on".into(),
+            attributes: serde_json::Map::new(),
+        })
+        .await?;
+    assert_eq!(response.state.entity_id, "sensor.big");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_post_states_uncompressed_by_default_async() -> Result<(), Box<dyn std::error::Error>>
+{
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "POST", "/api/states/sensor.big")
+        .match_header("content-encoding", Matcher::Missing)
+        .with_body(STATE_BODY)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let response = client.post_states(large_state_params()).await?;
+    assert_eq!(response.state.entity_id, "sensor.big");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
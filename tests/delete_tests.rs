@@ -0,0 +1,72 @@
+use home_assistant_rest::{errors, Client};
+use mockito::{Mock, ServerGuard};
+
+fn create_mock_server(server: &mut ServerGuard, endpoint: &str) -> Mock {
+    server
+        .mock("DELETE", endpoint)
+        .match_header("content-type", "application/json")
+        .match_header("Authorization", "Bearer test_token")
+}
+
+#[tokio::test]
+async fn test_delete_state_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/states/sensor.ephemeral")
+        .with_status(200)
+        .with_body(r#"{"message": "Entity removed."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let existed = client.delete_state("sensor.ephemeral").await?;
+
+    assert!(existed);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_state_not_found_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/states/sensor.missing")
+        .with_status(404)
+        .with_body(r#"{"message": "Entity not found."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let existed = client.delete_state("sensor.missing").await?;
+
+    assert!(!existed);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_state_unauthorized_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/states/sensor.ephemeral")
+        .with_status(401)
+        .with_body("401: Unauthorized")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let result = client.delete_state("sensor.ephemeral").await;
+
+    assert!(matches!(
+        result,
+        Err(errors::Error::ApiErrorResponse { status, .. }) if status == 401
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use std::{
+    net::TcpListener,
+    thread,
+    time::{Duration, Instant},
+};
+
+use home_assistant_rest::{errors, post, Client, ClientOptions};
+use mockito::{Mock, Server, ServerOpts};
+
+/// Returns a port on localhost that nothing is listening on
+fn unused_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Returns the URL of a server that accepts connections but never responds
+fn unresponsive_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    // Keep the accepted connections open without ever reading from them
+    thread::spawn(move || {
+        let mut connections = Vec::new();
+        for connection in listener.incoming() {
+            connections.push(connection);
+        }
+    });
+
+    url
+}
+
+async fn create_api_status_mock(server: &mut Server) -> Mock {
+    server
+        .mock("GET", "/api/")
+        .match_header("Authorization", "Bearer test_token")
+        .with_body(r#"{"message": "API running."}"#)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn test_failover_to_external_url_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut external = mockito::Server::new_async().await;
+    let external_mock = create_api_status_mock(&mut external).await;
+
+    let internal_url = format!("http://127.0.0.1:{}", unused_port());
+    let client = Client::with_urls(
+        [internal_url.as_str(), external.url().as_str()],
+        "test_token",
+        ClientOptions::default(),
+    )?;
+
+    assert_eq!(client.active_url().as_str(), format!("{}/", internal_url));
+
+    let api_status = client.get_api_status().await?;
+    assert_eq!(api_status.message, "API running.");
+    assert_eq!(client.active_url().as_str(), format!("{}/", external.url()));
+
+    external_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failback_to_internal_url_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut external = mockito::Server::new_async().await;
+    let external_mock = create_api_status_mock(&mut external).await;
+
+    let internal_port = unused_port();
+    let internal_url = format!("http://127.0.0.1:{}", internal_port);
+    let client = Client::with_urls(
+        [internal_url.as_str(), external.url().as_str()],
+        "test_token",
+        ClientOptions {
+            failback_interval: Duration::ZERO,
+            ..ClientOptions::default()
+        },
+    )?;
+
+    client.get_api_status().await?;
+    assert_eq!(client.active_url().as_str(), format!("{}/", external.url()));
+
+    let mut internal = mockito::Server::new_with_opts_async(ServerOpts {
+        port: internal_port,
+        ..ServerOpts::default()
+    })
+    .await;
+    let internal_mock = create_api_status_mock(&mut internal).await;
+
+    client.get_api_status().await?;
+    assert_eq!(client.active_url().as_str(), format!("{}/", internal_url));
+
+    external_mock.assert_async().await;
+    internal_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_failback_before_interval_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut external = mockito::Server::new_async().await;
+    let external_mock = create_api_status_mock(&mut external).await.expect(2);
+
+    let internal_url = format!("http://127.0.0.1:{}", unused_port());
+    let client = Client::with_urls(
+        [internal_url.as_str(), external.url().as_str()],
+        "test_token",
+        ClientOptions {
+            failback_interval: Duration::from_secs(3600),
+            ..ClientOptions::default()
+        },
+    )?;
+
+    client.get_api_status().await?;
+    client.get_api_status().await?;
+    assert_eq!(client.active_url().as_str(), format!("{}/", external.url()));
+
+    external_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detect_active_url_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut external = mockito::Server::new_async().await;
+    let external_mock = create_api_status_mock(&mut external).await;
+
+    let internal_url = format!("http://127.0.0.1:{}", unused_port());
+    let client = Client::with_urls(
+        [internal_url.as_str(), external.url().as_str()],
+        "test_token",
+        ClientOptions::default(),
+    )?;
+
+    let active_url = client.detect_active_url().await?;
+    assert_eq!(active_url.as_str(), format!("{}/", external.url()));
+    assert_eq!(client.active_url(), active_url);
+
+    external_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failover_from_non_routable_url_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut external = mockito::Server::new_async().await;
+    let external_mock = create_api_status_mock(&mut external).await;
+
+    // Addresses of TEST-NET-1 are not routed, connecting to them hangs until the connect timeout
+    let client = Client::with_urls(
+        ["http://192.0.2.1:8123", external.url().as_str()],
+        "test_token",
+        ClientOptions::default(),
+    )?;
+
+    let started = Instant::now();
+    let api_status = client.get_api_status().await?;
+
+    assert_eq!(api_status.message, "API running.");
+    assert_eq!(client.active_url().as_str(), format!("{}/", external.url()));
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    external_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failover_after_get_timeout_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut external = mockito::Server::new_async().await;
+    let external_mock = create_api_status_mock(&mut external).await;
+
+    let client = Client::with_urls(
+        [unresponsive_url().as_str(), external.url().as_str()],
+        "test_token",
+        ClientOptions {
+            timeout: Some(Duration::from_millis(200)),
+            ..ClientOptions::default()
+        },
+    )?;
+
+    let api_status = client.get_api_status().await?;
+    assert_eq!(api_status.message, "API running.");
+    assert_eq!(client.active_url().as_str(), format!("{}/", external.url()));
+
+    external_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_no_failover_after_post_timeout_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut external = mockito::Server::new_async().await;
+    let external_mock = external
+        .mock("POST", "/api/events/event_test_type")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let internal_url = unresponsive_url();
+    let client = Client::with_urls(
+        [internal_url.as_str(), external.url().as_str()],
+        "test_token",
+        ClientOptions {
+            timeout: Some(Duration::from_millis(200)),
+            ..ClientOptions::default()
+        },
+    )?;
+
+    let event_response = client
+        .post_events(post::EventParams {
+            event_type: "event_test_type".to_owned(),
+            event_data: None,
+        })
+        .await;
+
+    assert!(matches!(
+        event_response,
+        Err(errors::Error::RequestFailed(ref err)) if err.is_timeout()
+    ));
+    assert_eq!(client.active_url().as_str(), format!("{}/", internal_url));
+
+    external_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_all_urls_unreachable_async() -> Result<(), Box<dyn std::error::Error>> {
+    let client = Client::with_urls(
+        [
+            format!("http://127.0.0.1:{}", unused_port()),
+            format!("http://127.0.0.1:{}", unused_port()),
+        ],
+        "test_token",
+        ClientOptions::default(),
+    )?;
+
+    let api_status = client.get_api_status().await;
+    assert!(matches!(api_status, Err(errors::Error::RequestFailed(_))));
+
+    Ok(())
+}
+
+#[test]
+fn test_no_urls() {
+    let client = Client::with_urls(Vec::<String>::new(), "test_token", ClientOptions::default());
+    assert!(matches!(client, Err(errors::Error::NoUrls)));
+}
@@ -1,7 +1,17 @@
-use std::vec;
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+    time::Duration,
+    vec,
+};
 
 use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use futures_util::TryStreamExt;
 use home_assistant_rest::{
+    errors,
     get::{self, CalendarsParams, DateVariant},
     Client, StateEnum,
 };
@@ -149,6 +159,92 @@ async fn test_good_config_async() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_modern_config_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/config")
+        .match_query("")
+        .with_body(
+            r#"
+        {
+            "allowlist_external_dirs": ["/config/www", "/media"],
+            "allowlist_external_urls": ["https://example.com/"],
+            "components": ["api", "frontend", "sensor"],
+            "config_dir": "/config",
+            "config_source": "storage",
+            "country": "CH",
+            "currency": "CHF",
+            "debug": false,
+            "elevation": 510,
+            "external_url": "https://home.example.com",
+            "internal_url": null,
+            "language": "de",
+            "latitude": 45.8781529,
+            "location_name": "Home",
+            "longitude": 8.458853651,
+            "radius": 100,
+            "recovery_mode": false,
+            "safe_mode": false,
+            "state": "RUNNING",
+            "time_zone": "Europe/Zurich",
+            "unit_system": {
+                "accumulated_precipitation": "mm",
+                "area": "m\u00b2",
+                "length": "km",
+                "mass": "g",
+                "pressure": "Pa",
+                "temperature": "\u00b0C",
+                "volume": "L",
+                "wind_speed": "m/s",
+                "luminosity": "lx"
+            },
+            "version": "2024.6.0",
+            "whitelist_external_dirs": ["/config/www", "/media"],
+            "future_field": {"enabled": true}
+        }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let config = client.get_config().await?;
+
+    assert_eq!(
+        config.allowlist_external_dirs,
+        vec!["/config/www", "/media"]
+    );
+    assert_eq!(config.allowlist_external_urls, vec!["https://example.com/"]);
+    assert_eq!(config.config_source, Some(get::ConfigSource::Storage));
+    assert_eq!(config.country.as_deref(), Some("CH"));
+    assert_eq!(config.currency.as_deref(), Some("CHF"));
+    assert_eq!(config.debug, Some(false));
+    assert_eq!(
+        config.external_url.as_deref(),
+        Some("https://home.example.com")
+    );
+    assert_eq!(config.internal_url, None);
+    assert_eq!(config.language.as_deref(), Some("de"));
+    assert_eq!(config.radius, Some(100));
+    assert_eq!(config.recovery_mode, Some(false));
+    assert_eq!(config.safe_mode, Some(false));
+    assert_eq!(config.state, Some(get::CoreState::Running));
+    assert_eq!(
+        config.unit_system.accumulated_precipitation.as_deref(),
+        Some("mm")
+    );
+    assert_eq!(config.unit_system.area.as_deref(), Some("m\u{00b2}"));
+    assert_eq!(config.unit_system.pressure.as_deref(), Some("Pa"));
+    assert_eq!(config.unit_system.wind_speed.as_deref(), Some("m/s"));
+    assert_eq!(config.unit_system.other["luminosity"], "lx");
+    assert_eq!(config.other.len(), 1);
+    assert_eq!(config.other["future_field"]["enabled"], true);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_good_events_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
@@ -217,9 +313,220 @@ async fn test_good_services_async() -> Result<(), Box<dyn std::error::Error>> {
 
     assert_eq!(services.len(), 2);
     assert_eq!(services[0].domain, "browser");
-    assert_eq!(services[0].services, vec!["browse_url"]);
+    assert_eq!(services[0].services.len(), 1);
+    assert!(services[0].services.contains_key("browse_url"));
     assert_eq!(services[1].domain, "keyboard");
-    assert_eq!(services[1].services, vec!["volume_up", "volume_down"]);
+    assert_eq!(services[1].services.len(), 2);
+    assert!(services[1].services.contains_key("volume_up"));
+    assert!(services[1].services.contains_key("volume_down"));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_schemas_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services")
+        .match_query("")
+        .with_body(
+            r#"
+        [
+            {
+                "domain": "light",
+                "services": {
+                    "turn_on": {
+                        "name": "Turn on",
+                        "description": "Turns on one or more lights.",
+                        "fields": {
+                            "transition": {
+                                "filter": {"supported_features": [32]},
+                                "selector": {"number": {"min": 0, "max": 300, "unit_of_measurement": "seconds"}},
+                                "name": "Transition",
+                                "example": 60
+                            },
+                            "advanced_fields": {
+                                "collapsed": true,
+                                "fields": {
+                                    "effect": {"selector": {"text": null}, "name": "Effect"},
+                                    "flash": {
+                                        "selector": {"select": {"options": ["long", {"label": "Short", "value": "short"}]}}
+                                    }
+                                }
+                            }
+                        },
+                        "target": {"entity": [{"domain": ["light"]}]}
+                    }
+                }
+            },
+            {
+                "domain": "weather",
+                "services": {
+                    "get_forecasts": {
+                        "name": "Get forecasts",
+                        "fields": {
+                            "type": {
+                                "required": true,
+                                "selector": {"select": {"options": ["daily", "hourly"], "translation_key": "forecast_type"}}
+                            }
+                        },
+                        "target": {"entity": {"domain": "weather", "supported_features": [1, 2]}},
+                        "response": {"optional": false}
+                    },
+                    "reload": {
+                        "fields": {
+                            "now": {"selector": {"boolean": null}},
+                            "where": {"selector": {"location": {"radius": true}}}
+                        }
+                    }
+                }
+            }
+        ]"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let services = client.get_services().await?;
+
+    let turn_on = &services[0].services["turn_on"];
+    assert_eq!(turn_on.name.as_deref(), Some("Turn on"));
+    assert_eq!(turn_on.response_support(), get::ResponseSupport::None);
+    assert_eq!(turn_on.all_fields().count(), 3);
+    assert_eq!(
+        turn_on.target.as_ref().unwrap().entity[0].domain,
+        vec!["light"]
+    );
+
+    match &turn_on.fields["transition"] {
+        get::ServiceFieldEntry::Field(field) => {
+            assert_eq!(field.example, Some(serde_json::json!(60)));
+            match field.selector {
+                Some(get::Selector::Number(ref number)) => {
+                    assert_eq!(number.max, Some(300.0));
+                    assert_eq!(number.unit_of_measurement.as_deref(), Some("seconds"));
+                }
+                ref other => panic!("unexpected selector: {:?}", other),
+            }
+        }
+        other => panic!("unexpected entry: {:?}", other),
+    }
+
+    match &turn_on.fields["advanced_fields"] {
+        get::ServiceFieldEntry::Section(section) => {
+            assert!(section.collapsed);
+            assert!(matches!(
+                section.fields["effect"].selector,
+                Some(get::Selector::Text(_))
+            ));
+            match section.fields["flash"].selector {
+                Some(get::Selector::Select(ref select)) => assert_eq!(
+                    select.options,
+                    vec![
+                        get::SelectOption {
+                            value: "long".to_owned(),
+                            label: "long".to_owned()
+                        },
+                        get::SelectOption {
+                            value: "short".to_owned(),
+                            label: "Short".to_owned()
+                        }
+                    ]
+                ),
+                ref other => panic!("unexpected selector: {:?}", other),
+            }
+        }
+        other => panic!("unexpected entry: {:?}", other),
+    }
+
+    let get_forecasts = &services[1].services["get_forecasts"];
+    assert_eq!(get_forecasts.response_support(), get::ResponseSupport::Only);
+    let target = get_forecasts.target.as_ref().unwrap();
+    assert_eq!(target.entity[0].domain, vec!["weather"]);
+    assert_eq!(target.entity[0].supported_features.len(), 2);
+    let (_, forecast_type) = get_forecasts.all_fields().next().unwrap();
+    assert!(forecast_type.required);
+
+    let reload = &services[1].services["reload"];
+    let fields = reload
+        .all_fields()
+        .map(|(name, field)| (name.as_str(), field))
+        .collect::<HashMap<_, _>>();
+    assert!(matches!(
+        fields["now"].selector,
+        Some(get::Selector::Boolean)
+    ));
+    assert!(matches!(
+        fields["where"].selector,
+        Some(get::Selector::Other { ref kind, .. }) if kind == "location"
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_schemas_unknown_modes_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services")
+        .match_query("")
+        .with_body(
+            r#"
+        [
+            {
+                "domain": "custom_fan",
+                "services": {
+                    "set_speed": {
+                        "fields": {
+                            "speed": {"selector": {"number": {"min": 0, "max": 10, "mode": "knob"}}},
+                            "preset": {"selector": {"select": {"options": ["eco"], "mode": "carousel"}}}
+                        }
+                    }
+                }
+            },
+            {
+                "domain": "homeassistant",
+                "services": {
+                    "restart": {"name": "Restart", "fields": {}}
+                }
+            }
+        ]"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let services = client.get_services().await?;
+
+    assert_eq!(services.len(), 2);
+
+    let set_speed = &services[0].services["set_speed"];
+    let fields = set_speed
+        .all_fields()
+        .map(|(name, field)| (name.as_str(), field))
+        .collect::<HashMap<_, _>>();
+    match fields["speed"].selector {
+        Some(get::Selector::Number(ref number)) => {
+            assert_eq!(number.mode, Some(get::NumberSelectorMode::Unknown));
+            assert_eq!(number.max, Some(10.0));
+        }
+        ref other => panic!("unexpected selector: {:?}", other),
+    }
+    match fields["preset"].selector {
+        Some(get::Selector::Select(ref select)) => {
+            assert_eq!(select.mode, Some(get::SelectSelectorMode::Unknown));
+            assert_eq!(select.options.len(), 1);
+        }
+        ref other => panic!("unexpected selector: {:?}", other),
+    }
+    assert_eq!(
+        services[1].services["restart"].name.as_deref(),
+        Some("Restart")
+    );
 
     mock_server.assert_async().await;
 
@@ -230,18 +537,15 @@ async fn test_good_services_async() -> Result<(), Box<dyn std::error::Error>> {
 async fn test_good_history_period_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
 
-    // TODO: Figure out why match_query doesn't work
-    let mock_server = create_mock_server(
-        &mut server,
-        "/api/history/period/2016-12-29T11:22:33+02:00?end_time=2016-12-30T10:11:22+02:00",
-    )
-    // .match_query(mockito::Matcher::AllOf(vec![mockito::Matcher::UrlEncoded(
-    //     "end_time".to_owned(),
-    //     "2016-12-30T10:11:22+02:00".to_owned(),
-    // )]))
-    .with_status(200)
-    .with_body(
-        r#"
+    let mock_server =
+        create_mock_server(&mut server, "/api/history/period/2016-12-29T11:22:33+02:00")
+            .match_query(mockito::Matcher::AllOf(vec![mockito::Matcher::UrlEncoded(
+                "end_time".to_owned(),
+                "2016-12-30T10:11:22+02:00".to_owned(),
+            )]))
+            .with_status(200)
+            .with_body(
+                r#"
     [
         [
             {
@@ -266,9 +570,9 @@ async fn test_good_history_period_async() -> Result<(), Box<dyn std::error::Erro
             }
         ]
     ]"#,
-    )
-    .create_async()
-    .await;
+            )
+            .create_async()
+            .await;
 
     let start_time = FixedOffset::east_opt(2 * 3600)
         .unwrap()
@@ -291,17 +595,255 @@ async fn test_good_history_period_async() -> Result<(), Box<dyn std::error::Erro
 
     assert_eq!(history.len(), 1);
     assert_eq!(history[0].len(), 2);
+    assert_eq!(history[0][0].entity_id, "sensor.weather_temperature");
+    assert_eq!(history[0][0].friendly_name(), Some("Weather Temperature"));
+    assert_eq!(history[0][0].unit_of_measurement(), Some("\u{00b0}C"));
+    assert_eq!(history[0][0].state, Some(StateEnum::Decimal(-3.9)));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_history_series_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server =
+        create_mock_server(&mut server, "/api/history/period/2024-05-01T00:00:00+00:00")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded(
+                    "filter_entity_ids".to_owned(),
+                    "sensor.temperature,light.kitchen".to_owned(),
+                ),
+                mockito::Matcher::UrlEncoded("minimal_response".to_owned(), "true".to_owned()),
+                mockito::Matcher::UrlEncoded("no_attributes".to_owned(), "true".to_owned()),
+            ]))
+            .with_body(
+                r#"
+        [
+            [
+                {
+                    "entity_id": "sensor.temperature",
+                    "state": "21.5",
+                    "last_changed": "2024-05-01T00:00:00+00:00",
+                    "last_updated": "2024-05-01T00:00:00+00:00"
+                },
+                {"state": "22.0", "last_changed": "2024-05-01T01:00:00+00:00"},
+                {"state": "unavailable", "last_changed": "2024-05-01T02:00:00+00:00"}
+            ],
+            [
+                {
+                    "entity_id": "light.kitchen",
+                    "state": "off",
+                    "last_changed": "2024-05-01T00:00:00+00:00",
+                    "last_updated": "2024-05-01T00:00:00+00:00"
+                },
+                {"state": "on", "last_changed": "2024-05-01T18:30:00+00:00"}
+            ]
+        ]"#,
+            )
+            .create_async()
+            .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let series = client
+        .get_history_series(get::HistoryParams {
+            filter_entity_ids: Some(vec![
+                "sensor.temperature".to_owned(),
+                "light.kitchen".to_owned(),
+            ]),
+            start_time: Some(
+                FixedOffset::east_opt(0)
+                    .unwrap()
+                    .with_ymd_and_hms(2024, 5, 1, 0, 0, 0)
+                    .unwrap(),
+            ),
+            ..get::HistoryParams::default()
+        })
+        .await?;
+
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0].entity_id, "sensor.temperature");
+    assert_eq!(series[0].points.len(), 3);
+    assert_eq!(series[0].points[1].1, StateEnum::Decimal(22.0));
+    assert_eq!(series[1].entity_id, "light.kitchen");
     assert_eq!(
-        history[0][0].entity_id.as_deref(),
-        Some("sensor.weather_temperature")
+        series[1].points[1],
+        (
+            FixedOffset::east_opt(0)
+                .unwrap()
+                .with_ymd_and_hms(2024, 5, 1, 18, 30, 0)
+                .unwrap(),
+            StateEnum::String("on".to_owned())
+        )
+    );
+
+    let history = get::History::from(series);
+    let evening = FixedOffset::east_opt(0)
+        .unwrap()
+        .with_ymd_and_hms(2024, 5, 1, 20, 0, 0)
+        .unwrap();
+
+    assert_eq!(
+        history.entity_ids().collect::<Vec<_>>(),
+        vec!["light.kitchen", "sensor.temperature"]
+    );
+    assert_eq!(
+        history.state_at("light.kitchen", evening),
+        Some(&StateEnum::String("on".to_owned()))
+    );
+    assert_eq!(
+        history
+            .get("sensor.temperature")
+            .unwrap()
+            .intervals(evening)
+            .last()
+            .unwrap()
+            .end,
+        evening
     );
-    assert_eq!(history[0][0].state, Some(StateEnum::Decimal(-3.9)));
 
     mock_server.assert_async().await;
 
     Ok(())
 }
 
+#[tokio::test]
+async fn test_history_chunked_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let first_window =
+        create_mock_server(&mut server, "/api/history/period/2024-05-01T00:00:00+00:00")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("filter_entity_ids".to_owned(), "light.kitchen".to_owned()),
+                mockito::Matcher::UrlEncoded(
+                    "end_time".to_owned(),
+                    "2024-05-02T00:00:00+00:00".to_owned(),
+                ),
+                mockito::Matcher::UrlEncoded("minimal_response".to_owned(), "true".to_owned()),
+            ]))
+            .with_body(
+                r#"
+        [
+            [
+                {"entity_id": "light.kitchen", "state": "off", "last_changed": "2024-05-01T00:00:00+00:00"},
+                {"state": "on", "last_changed": "2024-05-01T18:30:00+00:00"}
+            ]
+        ]"#,
+            )
+            .create_async()
+            .await;
+
+    let second_window =
+        create_mock_server(&mut server, "/api/history/period/2024-05-02T00:00:00+00:00")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "end_time".to_owned(),
+                "2024-05-02T12:00:00+00:00".to_owned(),
+            ))
+            .with_body(
+                r#"
+        [
+            [
+                {"entity_id": "light.kitchen", "state": "on", "last_changed": "2024-05-02T00:00:00+00:00"},
+                {"state": "off", "last_changed": "2024-05-02T01:15:00+00:00"}
+            ]
+        ]"#,
+            )
+            .create_async()
+            .await;
+
+    let utc = FixedOffset::east_opt(0).unwrap();
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let history = client
+        .get_history_chunked(
+            get::HistoryParams {
+                filter_entity_ids: Some(vec!["light.kitchen".to_owned()]),
+                start_time: Some(utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()),
+                end_time: Some(utc.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap()),
+                ..get::HistoryParams::default()
+            },
+            get::ChunkOptions {
+                concurrency: 2,
+                ..get::ChunkOptions::default()
+            },
+        )
+        .await?;
+
+    assert_eq!(
+        history.get("light.kitchen").unwrap().points,
+        vec![
+            (
+                utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap(),
+                StateEnum::String("off".to_owned())
+            ),
+            (
+                utc.with_ymd_and_hms(2024, 5, 1, 18, 30, 0).unwrap(),
+                StateEnum::String("on".to_owned())
+            ),
+            (
+                utc.with_ymd_and_hms(2024, 5, 2, 1, 15, 0).unwrap(),
+                StateEnum::String("off".to_owned())
+            ),
+        ]
+    );
+
+    first_window.assert_async().await;
+    second_window.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_history_chunked_default_end_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    // Like Home Assistant, the period ends one day after the start without an end time
+    let mut windows = Vec::new();
+    for (start, end) in [
+        ("2024-05-01T00:00:00+00:00", "2024-05-01T12:00:00+00:00"),
+        ("2024-05-01T12:00:00+00:00", "2024-05-02T00:00:00+00:00"),
+    ] {
+        windows.push(
+            create_mock_server(&mut server, &format!("/api/history/period/{}", start))
+                .match_query(mockito::Matcher::UrlEncoded(
+                    "end_time".to_owned(),
+                    end.to_owned(),
+                ))
+                .with_body("[]")
+                .create_async()
+                .await,
+        );
+    }
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let history = client
+        .get_history_chunked(
+            get::HistoryParams {
+                start_time: Some(
+                    FixedOffset::east_opt(0)
+                        .unwrap()
+                        .with_ymd_and_hms(2024, 5, 1, 0, 0, 0)
+                        .unwrap(),
+                ),
+                ..get::HistoryParams::default()
+            },
+            get::ChunkOptions {
+                window: chrono::TimeDelta::hours(12),
+                ..get::ChunkOptions::default()
+            },
+        )
+        .await?;
+
+    assert!(history.is_empty());
+
+    for window in windows {
+        window.assert_async().await;
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_good_logbook_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
@@ -429,6 +971,257 @@ async fn test_good_logbook_async() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_logbook_context_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/logbook/2024-05-01T00:00:00+00:00")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded(
+                "entity".to_owned(),
+                "light.kitchen,light.porch".to_owned(),
+            ),
+            mockito::Matcher::UrlEncoded("period".to_owned(), "7".to_owned()),
+        ]))
+        .with_body(
+            r#"
+        [
+            {
+                "when": "2024-05-01T18:00:00.123456+00:00",
+                "state": "on",
+                "entity_id": "light.kitchen",
+                "name": "Kitchen",
+                "icon": "mdi:lightbulb",
+                "context_id": "01HWS4ZQ0Y4A1C6N7Y9B2C3D4E",
+                "context_user_id": "ae03ad0cefa6247baf4178ffce416910",
+                "context_event_type": "call_service",
+                "context_domain": "light",
+                "context_service": "turn_on"
+            },
+            {
+                "when": "2024-05-01T22:30:00+00:00",
+                "state": "off",
+                "entity_id": "light.porch",
+                "name": "Porch",
+                "context_event_type": "automation_triggered",
+                "context_domain": "automation",
+                "context_entity_id": "automation.lights_out",
+                "context_entity_id_name": "Lights out",
+                "context_name": "Lights out",
+                "context_message": "triggered by time",
+                "context_source": "time"
+            }
+        ]"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let logbook = client
+        .get_logbook(get::LogbookParams {
+            entity_ids: vec!["light.kitchen".to_owned(), "light.porch".to_owned()],
+            start_time: Some(
+                FixedOffset::east_opt(0)
+                    .unwrap()
+                    .with_ymd_and_hms(2024, 5, 1, 0, 0, 0)
+                    .unwrap(),
+            ),
+            period_days: Some(7),
+            ..get::LogbookParams::default()
+        })
+        .await?;
+
+    assert_eq!(logbook.len(), 2);
+
+    assert_eq!(logbook[0].state, Some(StateEnum::String("on".to_owned())));
+    assert_eq!(logbook[0].icon.as_deref(), Some("mdi:lightbulb"));
+    assert_eq!(
+        logbook[0].context_user_id.as_deref(),
+        Some("ae03ad0cefa6247baf4178ffce416910")
+    );
+    assert_eq!(
+        logbook[0].context_event_type.as_deref(),
+        Some("call_service")
+    );
+    assert_eq!(logbook[0].context_domain.as_deref(), Some("light"));
+    assert_eq!(logbook[0].context_service.as_deref(), Some("turn_on"));
+
+    assert_eq!(logbook[1].context_user_id, None);
+    assert_eq!(
+        logbook[1].context_entity_id.as_deref(),
+        Some("automation.lights_out")
+    );
+    assert_eq!(logbook[1].context_name.as_deref(), Some("Lights out"));
+    assert_eq!(logbook[1].context_source.as_deref(), Some("time"));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logbook_period_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/logbook/2024-05-01T00:00:00+00:00")
+        .match_query(mockito::Matcher::Exact("period=3".to_owned()))
+        .with_body("[]")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let logbook = client
+        .get_logbook(get::LogbookParams {
+            start_time: Some(
+                FixedOffset::east_opt(0)
+                    .unwrap()
+                    .with_ymd_and_hms(2024, 5, 1, 0, 0, 0)
+                    .unwrap(),
+            ),
+            period_days: Some(3),
+            ..get::LogbookParams::default()
+        })
+        .await?;
+
+    assert!(logbook.is_empty());
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logbook_chunked_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    // Like Home Assistant, a period of 2 days ending one day after the timestamp: 04-30 to 05-02
+    let first_window = create_mock_server(&mut server, "/api/logbook/2024-04-30T00:00:00+00:00")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("entity".to_owned(), "light.kitchen".to_owned()),
+            mockito::Matcher::UrlEncoded(
+                "end_time".to_owned(),
+                "2024-05-01T00:00:00+00:00".to_owned(),
+            ),
+        ]))
+        .with_body(
+            r#"
+        [
+            {"when": "2024-04-30T18:00:00+00:00", "state": "on", "entity_id": "light.kitchen"},
+            {"when": "2024-05-01T00:00:00+00:00", "state": "off", "entity_id": "light.kitchen"}
+        ]"#,
+        )
+        .create_async()
+        .await;
+
+    let second_window = create_mock_server(&mut server, "/api/logbook/2024-05-01T00:00:00+00:00")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "end_time".to_owned(),
+            "2024-05-02T00:00:00+00:00".to_owned(),
+        ))
+        .with_body(
+            r#"
+        [
+            {"when": "2024-05-01T00:00:00+00:00", "state": "off", "entity_id": "light.kitchen"},
+            {"when": "2024-05-01T19:00:00+00:00", "state": "on", "entity_id": "light.kitchen"}
+        ]"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let logbook = client
+        .get_logbook_chunked(
+            get::LogbookParams {
+                entity_ids: vec!["light.kitchen".to_owned()],
+                start_time: Some(
+                    FixedOffset::east_opt(0)
+                        .unwrap()
+                        .with_ymd_and_hms(2024, 5, 1, 0, 0, 0)
+                        .unwrap(),
+                ),
+                period_days: Some(2),
+                ..get::LogbookParams::default()
+            },
+            get::ChunkOptions::default(),
+        )
+        .await?;
+
+    assert_eq!(
+        logbook
+            .iter()
+            .map(|entry| entry.state.clone().unwrap())
+            .collect::<Vec<_>>(),
+        vec![
+            StateEnum::String("on".to_owned()),
+            StateEnum::String("off".to_owned()),
+            StateEnum::String("on".to_owned()),
+        ]
+    );
+
+    first_window.assert_async().await;
+    second_window.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logbook_chunked_period_out_of_range_async() -> Result<(), Box<dyn std::error::Error>>
+{
+    let server = mockito::Server::new_async().await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let logbook = client
+        .get_logbook_chunked(
+            get::LogbookParams {
+                start_time: Some(
+                    FixedOffset::east_opt(0)
+                        .unwrap()
+                        .with_ymd_and_hms(2024, 5, 1, 0, 0, 0)
+                        .unwrap(),
+                ),
+                period_days: Some(u32::MAX),
+                ..get::LogbookParams::default()
+            },
+            get::ChunkOptions::default(),
+        )
+        .await;
+
+    assert!(matches!(logbook, Err(errors::Error::PeriodOutOfRange)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_timezone_offset_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    // The `+` of the offset must be sent as `%2B`, a bare `+` is decoded as a space by the server
+    let mock_server = create_mock_server(&mut server, "/api/logbook/2024-05-01T00:00:00+02:00")
+        .match_query(mockito::Matcher::UrlEncoded(
+            "end_time".to_owned(),
+            "2024-05-02T00:00:00+02:00".to_owned(),
+        ))
+        .with_body("[]")
+        .create_async()
+        .await;
+
+    let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let logbook = client
+        .get_logbook(get::LogbookParams {
+            start_time: Some(offset.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap()),
+            end_time: Some(offset.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap()),
+            ..get::LogbookParams::default()
+        })
+        .await?;
+
+    assert!(logbook.is_empty());
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_good_states_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
@@ -556,6 +1349,56 @@ async fn test_good_state_async() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_state_if_exists_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let found_mock = create_mock_server(&mut server, "/api/states/sun.sun")
+        .with_body(
+            r#"{
+                "attributes": {"friendly_name": "Sun"},
+                "entity_id": "sun.sun",
+                "last_changed": "2016-05-30T21:43:29.204838+00:00",
+                "last_updated": "2016-05-30T21:50:30.529465+00:00",
+                "state": "below_horizon"
+            }"#,
+        )
+        .create_async()
+        .await;
+    let missing_mock = create_mock_server(&mut server, "/api/states/sensor.removed")
+        .with_status(404)
+        .with_body(r#"{"message": "Entity not found."}"#)
+        .create_async()
+        .await;
+    let broken_mock = create_mock_server(&mut server, "/api/states/sensor.broken")
+        .with_status(500)
+        .with_body("500 Internal Server Error")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let state = client.get_states_of_entity_if_exists("sun.sun").await?;
+    assert_eq!(state.unwrap().friendly_name(), Some("Sun"));
+
+    let state = client
+        .get_states_of_entity_if_exists("sensor.removed")
+        .await?;
+    assert!(state.is_none());
+
+    let state = client.get_states_of_entity_if_exists("sensor.broken").await;
+    assert!(matches!(
+        state,
+        Err(errors::Error::ApiErrorResponse { status, .. }) if status == 500
+    ));
+
+    found_mock.assert_async().await;
+    missing_mock.assert_async().await;
+    broken_mock.assert_async().await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_good_error_log_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
@@ -584,6 +1427,76 @@ async fn test_good_error_log_async() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_error_log_entries_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/error_log")
+        .with_body(
+            r#"2024-05-01 08:00:00.123 INFO (MainThread) [homeassistant.setup] Setting up hue
+2024-05-01 08:00:05.456 WARNING (MainThread) [homeassistant.components.hue] Bridge is slow to respond
+2024-05-01 09:15:30.000 ERROR (MainThread) [homeassistant.components.hue.light] Error updating lights
+Traceback (most recent call last):
+  File "/usr/src/homeassistant/homeassistant/components/hue/light.py", line 42, in update
+TimeoutError
+2024-05-01 10:00:00.000 ERROR (Recorder) [homeassistant.components.recorder] Database is locked"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let log = client.get_error_log_entries().await?;
+
+    assert_eq!(log.entries.len(), 4);
+
+    let errors = log
+        .filter(get::ErrorLogFilter {
+            min_level: Some(get::LogLevel::Error),
+            ..get::ErrorLogFilter::default()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].message, "Error updating lights");
+    assert!(errors[0]
+        .traceback
+        .as_deref()
+        .unwrap()
+        .ends_with("TimeoutError"));
+
+    let hue = log
+        .filter(get::ErrorLogFilter {
+            min_level: Some(get::LogLevel::Warning),
+            integration: Some("hue".to_owned()),
+            ..get::ErrorLogFilter::default()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(hue.len(), 2);
+
+    let window = log
+        .filter(get::ErrorLogFilter {
+            since: Some(
+                NaiveDate::from_ymd_opt(2024, 5, 1)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0)
+                    .unwrap(),
+            ),
+            until: Some(
+                NaiveDate::from_ymd_opt(2024, 5, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 0, 0)
+                    .unwrap(),
+            ),
+            ..get::ErrorLogFilter::default()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(window.len(), 1);
+    assert_eq!(window[0].logger, "homeassistant.components.hue.light");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_good_calendars_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
@@ -624,8 +1537,11 @@ async fn test_good_calendars_async() -> Result<(), Box<dyn std::error::Error>> {
 async fn test_good_calendars_entity_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
 
-    // TODO: Figure out why match_query doesn't work
-    let mock_server = create_mock_server(&mut server, "/api/calendars/calendar.holidays?start=2022-05-01T07:00:00.000Z&end=2022-06-12T07:00:00.000Z")
+    let mock_server = create_mock_server(&mut server, "/api/calendars/calendar.holidays")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("start".to_owned(), "2022-05-01T07:00:00.000Z".to_owned()),
+            mockito::Matcher::UrlEncoded("end".to_owned(), "2022-06-12T07:00:00.000Z".to_owned()),
+        ]))
         .with_body(
             r#"
         [
@@ -730,3 +1646,237 @@ async fn test_good_calendars_entity_async() -> Result<(), Box<dyn std::error::Er
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_good_camera_proxy_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mut image = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    image.extend_from_slice(&640u32.to_be_bytes());
+    image.extend_from_slice(&480u32.to_be_bytes());
+
+    let mock_server = create_mock_server(&mut server, "/api/camera_proxy/camera.front_door")
+        .match_query("time=1704067200000")
+        .with_header("content-type", "image/png")
+        .with_body(&image)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let camera = client
+        .get_camera_proxy(get::CameraProxyParams {
+            entity_id: "camera.front_door".to_owned(),
+            timestamp: Some(
+                FixedOffset::east_opt(0)
+                    .unwrap()
+                    .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+                    .unwrap(),
+            ),
+        })
+        .await?;
+
+    assert_eq!(camera.content_type.as_deref(), Some("image/png"));
+    assert_eq!(
+        camera.dimensions,
+        Some(get::ImageDimensions {
+            width: 640,
+            height: 480
+        })
+    );
+    assert_eq!(camera.image.as_ref(), image.as_slice());
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_camera_proxy_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/camera_proxy/camera.missing")
+        .match_query("")
+        .with_status(404)
+        .with_body("404: Not Found")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let camera = client
+        .get_camera_proxy(get::CameraProxyParams {
+            entity_id: "camera.missing".to_owned(),
+            timestamp: None,
+        })
+        .await;
+
+    assert!(matches!(
+        camera,
+        Err(errors::Error::EntityNotFound(entity_id)) if entity_id == "camera.missing"
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+fn mjpeg_body(frames: &[&[u8]]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for frame in frames {
+        body.extend_from_slice(
+            format!(
+                "--frameboundary\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+                frame.len()
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(frame);
+        body.extend_from_slice(b"\r\n");
+    }
+    body
+}
+
+#[tokio::test]
+async fn test_good_camera_stream_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/camera_proxy_stream/camera.front_door")
+        .with_header(
+            "content-type",
+            "multipart/x-mixed-replace;boundary=--frameboundary",
+        )
+        .with_body(mjpeg_body(&[b"frame one", b"frame two", b"frame three"]))
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let frames = client
+        .get_camera_stream(get::CameraStreamParams {
+            entity_id: "camera.front_door".to_owned(),
+            min_frame_interval: None,
+        })
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].image.as_ref(), b"frame one");
+    assert_eq!(frames[0].content_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(frames[1].image.as_ref(), b"frame two");
+    assert_eq!(frames[2].image.as_ref(), b"frame three");
+    assert!(frames[0].timestamp <= frames[2].timestamp);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_throttled_camera_stream_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/camera_proxy_stream/camera.front_door")
+        .with_header(
+            "content-type",
+            "multipart/x-mixed-replace;boundary=--frameboundary",
+        )
+        .with_body(mjpeg_body(&[b"frame one", b"frame two", b"frame three"]))
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let frames = client
+        .get_camera_stream(get::CameraStreamParams {
+            entity_id: "camera.front_door".to_owned(),
+            min_frame_interval: Some(Duration::from_secs(3600)),
+        })
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].image.as_ref(), b"frame one");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dropped_camera_stream_closes_connection_async(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}", listener.local_addr()?);
+    let (closed_sender, closed_receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut connection, _) = listener.accept().unwrap();
+
+        let mut request = Vec::new();
+        let mut byte = [0; 1];
+        while !request.ends_with(b"\r\n\r\n") {
+            connection.read_exact(&mut byte).unwrap();
+            request.push(byte[0]);
+        }
+
+        connection
+            .write_all(
+                b"HTTP/1.1 200 OK\r\n\
+                  Content-Type: multipart/x-mixed-replace;boundary=--frameboundary\r\n\
+                  Connection: close\r\n\r\n",
+            )
+            .unwrap();
+        connection.write_all(&mjpeg_body(&[b"frame one"])).unwrap();
+        connection
+            .write_all(b"--frameboundary\r\nContent-Type: image/jpeg\r\n\r\n")
+            .unwrap();
+
+        // Keep sending the body of the second frame until the client hangs up
+        while connection.write_all(b"frame two").is_ok() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        closed_sender.send(()).unwrap();
+    });
+
+    let client = Client::new(url.as_str(), "test_token")?;
+    let mut stream = client
+        .get_camera_stream(get::CameraStreamParams {
+            entity_id: "camera.front_door".to_owned(),
+            min_frame_interval: None,
+        })
+        .await?;
+
+    let frame = stream.try_next().await?.unwrap();
+    assert_eq!(frame.image.as_ref(), b"frame one");
+
+    drop(stream);
+
+    tokio::task::spawn_blocking(move || closed_receiver.recv_timeout(Duration::from_secs(5)))
+        .await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_camera_stream_not_multipart_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/camera_proxy_stream/camera.front_door")
+        .with_header("content-type", "image/jpeg")
+        .with_body("frame")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let stream = client
+        .get_camera_stream(get::CameraStreamParams {
+            entity_id: "camera.front_door".to_owned(),
+            min_frame_interval: None,
+        })
+        .await;
+
+    assert!(matches!(stream, Err(errors::Error::UnexpectedResponse(_))));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
-use home_assistant_rest::{post, Client, StateEnum};
+use home_assistant_rest::{errors, post, Client, ConversationSession, StateEnum};
 use mockito::{Mock, ServerGuard};
 use serde_json::json;
 
@@ -40,12 +40,15 @@ async fn test_update_post_states1_async() -> Result<(), Box<dyn std::error::Erro
 
     let request = post::StateParams {
         entity_id: "sensor.sun".to_owned(),
-        state: "above_horizon".to_owned(),
-        attributes: HashMap::new(),
+        state: "above_horizon".into(),
+        attributes: serde_json::Map::new(),
     };
 
     let response = client.post_states(request).await?;
 
+    assert!(!response.created);
+    let response = response.state;
+
     let timezone = FixedOffset::east_opt(0).unwrap();
     assert_eq!(response.entity_id, "sensor.sun");
     assert_eq!(
@@ -63,7 +66,7 @@ async fn test_update_post_states1_async() -> Result<(), Box<dyn std::error::Erro
         .unwrap()
     );
     assert_eq!(
-        response.last_reported,
+        response.last_reported.unwrap(),
         NaiveDateTime::new(
             NaiveDate::from_ymd_opt(2024, 4, 25).unwrap(),
             NaiveTime::from_hms_nano_opt(23, 49, 34, 728_773_000).unwrap()
@@ -80,10 +83,13 @@ async fn test_update_post_states1_async() -> Result<(), Box<dyn std::error::Erro
         .and_local_timezone(timezone)
         .unwrap()
     );
-    assert_eq!(response.context.id, "01GYXD54C8D0YFJ6ASFDGJBJR9");
-    assert_eq!(response.context.parent_id, None);
     assert_eq!(
-        response.context.user_id,
+        response.context.as_ref().unwrap().id,
+        "01GYXD54C8D0YFJ6ASFDGJBJR9"
+    );
+    assert_eq!(response.context.as_ref().unwrap().parent_id, None);
+    assert_eq!(
+        response.context.as_ref().unwrap().user_id,
         Some("ae03ad0cefa6247baf4178ffce416910".to_string())
     );
 
@@ -120,12 +126,15 @@ async fn test_update_post_states2_async() -> Result<(), Box<dyn std::error::Erro
 
     let request = post::StateParams {
         entity_id: "climate.thermostat".to_owned(),
-        state: "cool".to_owned(),
-        attributes: HashMap::new(),
+        state: "cool".into(),
+        attributes: serde_json::Map::new(),
     };
 
     let response = client.post_states(request).await?;
 
+    assert!(!response.created);
+    let response = response.state;
+
     let timezone = FixedOffset::east_opt(0).unwrap();
     assert_eq!(response.entity_id, "climate.thermostat");
     assert_eq!(response.state, Some(StateEnum::String("cool".to_owned())));
@@ -140,7 +149,7 @@ async fn test_update_post_states2_async() -> Result<(), Box<dyn std::error::Erro
         .unwrap()
     );
     assert_eq!(
-        response.last_reported,
+        response.last_reported.unwrap(),
         NaiveDateTime::new(
             NaiveDate::from_ymd_opt(2024, 4, 26).unwrap(),
             NaiveTime::from_hms_nano_opt(2, 17, 56, 33_828_000).unwrap()
@@ -157,9 +166,12 @@ async fn test_update_post_states2_async() -> Result<(), Box<dyn std::error::Erro
         .and_local_timezone(timezone)
         .unwrap()
     );
-    assert_eq!(response.context.id, "01GYXJ6XE1008RBVG58E2NKJ3N");
-    assert_eq!(response.context.parent_id, None);
-    assert_eq!(response.context.user_id, None);
+    assert_eq!(
+        response.context.as_ref().unwrap().id,
+        "01GYXJ6XE1008RBVG58E2NKJ3N"
+    );
+    assert_eq!(response.context.as_ref().unwrap().parent_id, None);
+    assert_eq!(response.context.as_ref().unwrap().user_id, None);
 
     mock_server.assert_async().await;
 
@@ -172,6 +184,7 @@ async fn test_create_post_states_async() -> Result<(), Box<dyn std::error::Error
 
     let mock_server = create_mock_server(&mut server, "/api/states/sensor.test")
         .match_body(r#"{"state":"create_new","attributes":{}}"#)
+        .with_status(201)
         .with_body(
             r#"{
                 "entity_id":"sensor.test",
@@ -194,12 +207,15 @@ async fn test_create_post_states_async() -> Result<(), Box<dyn std::error::Error
 
     let request = post::StateParams {
         entity_id: "sensor.test".to_owned(),
-        state: "create_new".to_owned(),
-        attributes: HashMap::new(),
+        state: "create_new".into(),
+        attributes: serde_json::Map::new(),
     };
 
     let response = client.post_states(request).await?;
 
+    assert!(response.created);
+    let response = response.state;
+
     let timezone = FixedOffset::east_opt(0).unwrap();
     assert_eq!(response.entity_id, "sensor.test");
     assert_eq!(
@@ -217,7 +233,7 @@ async fn test_create_post_states_async() -> Result<(), Box<dyn std::error::Error
         .unwrap()
     );
     assert_eq!(
-        response.last_reported,
+        response.last_reported.unwrap(),
         NaiveDateTime::new(
             NaiveDate::from_ymd_opt(2024, 4, 26).unwrap(),
             NaiveTime::from_hms_nano_opt(2, 23, 35, 616_516_000).unwrap()
@@ -234,10 +250,13 @@ async fn test_create_post_states_async() -> Result<(), Box<dyn std::error::Error
         .and_local_timezone(timezone)
         .unwrap()
     );
-    assert_eq!(response.context.id, "01GYXJH920PEZGN2ZB0QRNY763");
-    assert_eq!(response.context.parent_id, None);
     assert_eq!(
-        response.context.user_id,
+        response.context.as_ref().unwrap().id,
+        "01GYXJH920PEZGN2ZB0QRNY763"
+    );
+    assert_eq!(response.context.as_ref().unwrap().parent_id, None);
+    assert_eq!(
+        response.context.as_ref().unwrap().user_id,
         Some("ae03ad0cefa6247baf4178ffce416910".to_string())
     );
 
@@ -246,6 +265,105 @@ async fn test_create_post_states_async() -> Result<(), Box<dyn std::error::Error
     Ok(())
 }
 
+#[tokio::test]
+async fn test_post_states_json_attributes_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/states/sensor.dimmer")
+        .match_body(mockito::Matcher::Json(json!({
+            "state": "128",
+            "attributes": {
+                "brightness": 128,
+                "options": ["low", "medium", "high"],
+                "extra": {"enabled": true}
+            }
+        })))
+        .with_status(201)
+        .with_body(
+            r#"{
+                "entity_id":"sensor.dimmer",
+                "state":"128",
+                "attributes":{"brightness":128,"options":["low","medium","high"],"extra":{"enabled":true}},
+                "last_changed":"2023-04-26T01:23:35.616516+00:00",
+                "last_reported":"2024-04-26T02:23:35.616516+00:00",
+                "last_updated":"2023-04-26T01:23:35.616516+00:00",
+                "context":{
+                    "id":"01GYXJH920PEZGN2ZB0QRNY763",
+                    "parent_id":null,
+                    "user_id":"ae03ad0cefa6247baf4178ffce416910"
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    #[derive(serde::Serialize)]
+    struct DimmerAttributes {
+        brightness: u8,
+        options: Vec<&'static str>,
+        extra: serde_json::Value,
+    }
+
+    let response = client
+        .post_states(post::StateParams {
+            entity_id: "sensor.dimmer".to_owned(),
+            state: 128.into(),
+            attributes: DimmerAttributes {
+                brightness: 128,
+                options: vec!["low", "medium", "high"],
+                extra: json!({"enabled": true}),
+            },
+        })
+        .await?;
+
+    assert!(response.created);
+    assert_eq!(response.state.state, Some(StateEnum::Integer(128)));
+    assert_eq!(response.state.attributes["brightness"], json!(128));
+    assert_eq!(
+        response.state.attributes["options"],
+        json!(["low", "medium", "high"])
+    );
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[cfg(feature = "serde_debugging")]
+#[tokio::test]
+async fn test_post_states_invalid_entity_with_debugging_async(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/states/not_an_entity")
+        .with_status(400)
+        .with_body(r#"{"message": "Invalid entity ID specified."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let response = client
+        .post_states_with_debugging(post::StateParams {
+            entity_id: "not_an_entity".to_owned(),
+            state: "on".into(),
+            attributes: serde_json::Map::new(),
+        })
+        .await;
+
+    assert!(matches!(
+        response,
+        Err(errors::DebuggingError::ApiErrorResponse { status, ref message })
+            if status == 400 && message == "Invalid entity ID specified."
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_event_type_with_body_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
@@ -318,6 +436,7 @@ async fn test_template1_async() -> Result<(), Box<dyn std::error::Error>> {
     let template_response = client
         .post_template(post::TemplateParams {
             template: "It is {{ now() }}!".to_owned(),
+            ..Default::default()
         })
         .await?;
 
@@ -343,6 +462,7 @@ async fn test_template2_async() -> Result<(), Box<dyn std::error::Error>> {
     let template_response = client
         .post_template(post::TemplateParams {
             template: "The sun is currently {{ states('sensor.sun') }}!".to_owned(),
+            ..Default::default()
         })
         .await?;
 
@@ -353,6 +473,126 @@ async fn test_template2_async() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_template_with_variables_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/template")
+        .match_body(mockito::Matcher::Json(json!({
+            "template": "{{ base * factor }}",
+            "variables": {"base": 21, "factor": 2}
+        })))
+        .with_body("42")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let mut variables = serde_json::Map::new();
+    variables.insert("base".to_owned(), json!(21));
+    variables.insert("factor".to_owned(), json!(2));
+
+    let rendered: i64 = client
+        .post_template_as(post::TemplateParams {
+            template: "{{ base * factor }}".to_owned(),
+            variables,
+        })
+        .await?;
+
+    assert_eq!(rendered, 42);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_template_typed_results_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let bool_mock = create_mock_server(&mut server, "/api/template")
+        .match_body(r#"{"template":"{{ is_state('sun.sun', 'above_horizon') }}"}"#)
+        .with_body("True")
+        .create_async()
+        .await;
+    let list_mock = create_mock_server(&mut server, "/api/template")
+        .match_body(
+            r#"{"template":"{{ states.light | map(attribute='entity_id') | list | tojson }}"}"#,
+        )
+        .with_body(r#"["light.kitchen", "light.porch"]"#)
+        .create_async()
+        .await;
+    let state_mock = create_mock_server(&mut server, "/api/template")
+        .match_body(r#"{"template":"{{ states('sensor.temperature') }}"}"#)
+        .with_body("21.5")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let above_horizon: bool = client
+        .post_template_as(post::TemplateParams {
+            template: "{{ is_state('sun.sun', 'above_horizon') }}".to_owned(),
+            ..Default::default()
+        })
+        .await?;
+    assert!(above_horizon);
+
+    let lights: Vec<String> = client
+        .post_template_as(post::TemplateParams {
+            template: "{{ states.light | map(attribute='entity_id') | list | tojson }}".to_owned(),
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(lights, vec!["light.kitchen", "light.porch"]);
+
+    let temperature: StateEnum = client
+        .post_template_as(post::TemplateParams {
+            template: "{{ states('sensor.temperature') }}".to_owned(),
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(temperature, StateEnum::Decimal(21.5));
+
+    bool_mock.assert_async().await;
+    list_mock.assert_async().await;
+    state_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_template_error_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/template")
+        .with_status(400)
+        .with_body(
+            r#"{"message": "Error rendering template: TemplateSyntaxError: unexpected '}'"}"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let result = client
+        .post_template(post::TemplateParams {
+            template: "{{ states('sensor.sun') }}}".to_owned(),
+            ..Default::default()
+        })
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(errors::Error::TemplateError(ref message))
+            if message == "Error rendering template: TemplateSyntaxError: unexpected '}'"
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_check_config_good_async() -> Result<(), Box<dyn std::error::Error>> {
     let mut server = mockito::Server::new_async().await;
@@ -366,8 +606,9 @@ async fn test_check_config_good_async() -> Result<(), Box<dyn std::error::Error>
 
     let config_check_response = client.post_config_check().await?;
 
-    assert_eq!(config_check_response.result, "valid");
-    assert_eq!(config_check_response.errors, None);
+    assert_eq!(config_check_response.result, post::CheckConfigResult::Valid);
+    assert!(config_check_response.errors.is_empty());
+    assert!(config_check_response.ensure_valid()?.is_empty());
 
     mock_server.assert_async().await;
 
@@ -387,13 +628,554 @@ async fn test_check_config_bad_async() -> Result<(), Box<dyn std::error::Error>>
 
     let config_check_response = client.post_config_check().await?;
 
-    assert_eq!(config_check_response.result, "invalid");
+    assert_eq!(
+        config_check_response.result,
+        post::CheckConfigResult::Invalid
+    );
     assert_eq!(
         config_check_response.errors,
-        Some("Platform error weather.darksky - Integration 'darksky' not found.".to_owned())
+        vec![post::ConfigIssue {
+            message: "Platform error weather.darksky - Integration 'darksky' not found.".to_owned(),
+            integration: Some("darksky".to_owned()),
+            file: None,
+            line: None,
+        }]
     );
 
+    match config_check_response.ensure_valid() {
+        Err(errors::Error::InvalidConfig(issues)) => assert_eq!(issues.len(), 1),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
     mock_server.assert_async().await;
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_check_config_warnings_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/config/core/check_config")
+        .with_body(
+            r#"{
+                "result": "invalid",
+                "errors": "Invalid config for 'sensor' at configuration.yaml, line 12: required key 'platform' not provided\nIntegration error: foo - Integration 'foo' not found.",
+                "warnings": "Invalid config for 'light' at lights.yaml, line 3: 'brightness' is deprecated"
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let config_check_response = client.post_config_check().await?;
+
+    assert!(!config_check_response.is_valid());
+    assert_eq!(config_check_response.errors.len(), 2);
+    assert_eq!(
+        config_check_response.errors[0].integration.as_deref(),
+        Some("sensor")
+    );
+    assert_eq!(
+        config_check_response.errors[0].file.as_deref(),
+        Some("configuration.yaml")
+    );
+    assert_eq!(config_check_response.errors[0].line, Some(12));
+    assert_eq!(
+        config_check_response.errors[1].integration.as_deref(),
+        Some("foo")
+    );
+    assert_eq!(config_check_response.warnings.len(), 1);
+    assert_eq!(
+        config_check_response.warnings[0].file.as_deref(),
+        Some("lights.yaml")
+    );
+    assert_eq!(config_check_response.warnings[0].line, Some(3));
+
+    let error = config_check_response.ensure_valid().unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("Configuration is invalid: Invalid config for 'sensor'"));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services/light/turn_on")
+        .match_body(mockito::Matcher::Json(json!({
+            "entity_id": ["light.kitchen"],
+            "area_id": ["living_room"],
+            "brightness": 128,
+            "color_name": "blue"
+        })))
+        .with_body(
+            r#"[
+                {
+                    "entity_id":"light.kitchen",
+                    "state":"on",
+                    "attributes":{"brightness":128,"friendly_name":"Kitchen"},
+                    "last_changed":"2023-04-25T23:49:34.728773+00:00",
+                    "last_reported":"2023-04-25T23:49:34.728773+00:00",
+                    "last_updated":"2023-04-25T23:49:34.728773+00:00",
+                    "context":{
+                        "id":"01GYXD54C8D0YFJ6ASFDGJBJR9",
+                        "parent_id":null,
+                        "user_id":"ae03ad0cefa6247baf4178ffce416910"
+                    }
+                }
+            ]"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let mut data = serde_json::Map::new();
+    data.insert("brightness".to_owned(), json!(128));
+    data.insert("color_name".to_owned(), json!("blue"));
+
+    let changed_states = client
+        .post_service(post::ServiceParams {
+            domain: "light".to_owned(),
+            service: "turn_on".to_owned(),
+            target: post::ServiceTarget {
+                entity_id: vec!["light.kitchen".to_owned()],
+                area_id: vec!["living_room".to_owned()],
+                ..post::ServiceTarget::default()
+            },
+            data,
+        })
+        .await?;
+
+    assert_eq!(changed_states.len(), 1);
+    assert_eq!(changed_states[0].entity_id, "light.kitchen");
+    assert_eq!(
+        changed_states[0].state,
+        Some(StateEnum::String("on".to_owned()))
+    );
+    assert_eq!(changed_states[0].attributes["brightness"], 128);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_without_data_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services/homeassistant/reload_all")
+        .match_body("{}")
+        .with_body("[]")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let changed_states = client
+        .post_service(post::ServiceParams {
+            domain: "homeassistant".to_owned(),
+            service: "reload_all".to_owned(),
+            ..post::ServiceParams::default()
+        })
+        .await?;
+
+    assert!(changed_states.is_empty());
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_service_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services/light/explode")
+        .with_status(400)
+        .with_body(r#"{"message": "Service light.explode not found."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let response = client
+        .post_service(post::ServiceParams {
+            domain: "light".to_owned(),
+            service: "explode".to_owned(),
+            ..post::ServiceParams::default()
+        })
+        .await;
+
+    assert!(matches!(
+        response,
+        Err(errors::Error::ApiErrorResponse { status, ref message })
+            if status == 400 && message == "Service light.explode not found."
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_with_response_async() -> Result<(), Box<dyn std::error::Error>> {
+    #[derive(serde::Deserialize, Debug)]
+    struct Forecast {
+        condition: String,
+        temperature: f64,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Forecasts {
+        forecast: Vec<Forecast>,
+    }
+
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services/weather/get_forecasts")
+        .match_query("return_response")
+        .match_body(mockito::Matcher::Json(json!({
+            "entity_id": ["weather.home"],
+            "type": "daily"
+        })))
+        .with_body(
+            r#"{
+                "changed_states": [],
+                "service_response": {
+                    "weather.home": {
+                        "forecast": [
+                            {"condition": "sunny", "temperature": 21.5},
+                            {"condition": "rainy", "temperature": 17.0}
+                        ]
+                    }
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let mut data = serde_json::Map::new();
+    data.insert("type".to_owned(), json!("daily"));
+
+    let response = client
+        .post_service_with_response::<HashMap<String, Forecasts>>(post::ServiceParams {
+            domain: "weather".to_owned(),
+            service: "get_forecasts".to_owned(),
+            target: post::ServiceTarget {
+                entity_id: vec!["weather.home".to_owned()],
+                ..post::ServiceTarget::default()
+            },
+            data,
+        })
+        .await?;
+
+    assert!(response.changed_states.is_empty());
+
+    let forecast = &response.service_response["weather.home"].forecast;
+    assert_eq!(forecast.len(), 2);
+    assert_eq!(forecast[0].condition, "sunny");
+    assert_eq!(forecast[0].temperature, 21.5);
+    assert_eq!(forecast[1].condition, "rainy");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_service_with_response_unsupported_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services/light/turn_on")
+        .match_query("return_response")
+        .with_status(400)
+        .with_body(r#"{"message": "Service does not support responses. Remove return_response from request."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let response = client
+        .post_service_with_response::<serde_json::Value>(post::ServiceParams {
+            domain: "light".to_owned(),
+            service: "turn_on".to_owned(),
+            ..post::ServiceParams::default()
+        })
+        .await;
+
+    assert!(matches!(
+        response,
+        Err(errors::Error::ApiErrorResponse { status, .. }) if status == 400
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[cfg(feature = "serde_debugging")]
+#[tokio::test]
+async fn test_unknown_service_with_debugging_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services/light/explode")
+        .with_status(400)
+        .with_body(r#"{"message": "Service light.explode not found."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let response = client
+        .post_service_with_debugging(post::ServiceParams {
+            domain: "light".to_owned(),
+            service: "explode".to_owned(),
+            ..post::ServiceParams::default()
+        })
+        .await;
+
+    assert!(matches!(
+        response,
+        Err(errors::DebuggingError::ApiErrorResponse { status, ref message })
+            if status == 400 && message == "Service light.explode not found."
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[cfg(feature = "serde_debugging")]
+#[tokio::test]
+async fn test_service_with_response_unsupported_with_debugging_async(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/services/light/turn_on")
+        .match_query("return_response")
+        .with_status(400)
+        .with_body(r#"{"message": "Service does not support responses. Remove return_response from request."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let response = client
+        .post_service_with_response_and_debugging::<serde_json::Value>(post::ServiceParams {
+            domain: "light".to_owned(),
+            service: "turn_on".to_owned(),
+            ..post::ServiceParams::default()
+        })
+        .await;
+
+    assert!(matches!(
+        response,
+        Err(errors::DebuggingError::ApiErrorResponse { status, .. }) if status == 400
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_intent_handle_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/intent/handle")
+        .match_body(r#"{"name":"HassTurnOn","data":{"name":"kitchen light"}}"#)
+        .with_body(
+            r#"{
+                "speech": {
+                    "plain": {"speech": "Turned on the light", "extra_data": null},
+                    "ssml": {"speech": "<speak>Turned on the light</speak>", "extra_data": null}
+                },
+                "card": {
+                    "simple": {"title": "Lights", "content": "Kitchen light is on"}
+                },
+                "language": "en",
+                "response_type": "action_done",
+                "data": {
+                    "targets": [{"name": "Kitchen", "type": "area", "id": "kitchen"}],
+                    "success": [{"name": "Kitchen Light", "type": "entity", "id": "light.kitchen"}],
+                    "failed": [{"name": "Kitchen Lamp", "type": "entity", "id": "light.kitchen_lamp"}]
+                }
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let mut data = serde_json::Map::new();
+    data.insert("name".to_owned(), json!("kitchen light"));
+
+    let response = client
+        .post_handle(post::IntentParams {
+            name: "HassTurnOn".to_owned(),
+            data,
+        })
+        .await?;
+
+    assert_eq!(response.response_type, post::IntentResponseType::ActionDone);
+    assert_eq!(
+        response.speech.plain.as_ref().map(|s| s.speech.as_str()),
+        Some("Turned on the light")
+    );
+    assert_eq!(
+        response.speech.ssml.as_ref().map(|s| s.speech.as_str()),
+        Some("<speak>Turned on the light</speak>")
+    );
+    let card = response.card.simple.as_ref().unwrap();
+    assert_eq!(card.title, "Lights");
+    assert_eq!(card.content, "Kitchen light is on");
+    assert_eq!(response.language.as_deref(), Some("en"));
+
+    assert_eq!(response.data.targets.len(), 1);
+    assert_eq!(
+        response.data.targets[0].target_type,
+        post::IntentTargetType::Area
+    );
+    assert_eq!(
+        response.data.success[0].id.as_deref(),
+        Some("light.kitchen")
+    );
+    assert_eq!(
+        response.data.failed[0].id.as_deref(),
+        Some("light.kitchen_lamp")
+    );
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_intent_handle_error_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "/api/intent/handle")
+        .with_body(
+            r#"{
+                "speech": {"plain": {"speech": "Sorry, I couldn't understand that", "extra_data": null}},
+                "card": {},
+                "language": "en",
+                "response_type": "error",
+                "data": {"code": "no_intent_match"}
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+
+    let response = client
+        .post_handle(post::IntentParams {
+            name: "Gibberish".to_owned(),
+            ..post::IntentParams::default()
+        })
+        .await?;
+
+    assert_eq!(response.response_type, post::IntentResponseType::Error);
+    assert_eq!(response.data.code.as_deref(), Some("no_intent_match"));
+    assert!(response.card.simple.is_none());
+    assert!(response.data.targets.is_empty());
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conversation_session_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let first_turn = create_mock_server(&mut server, "/api/conversation/process")
+        .match_body(mockito::Matcher::Json(json!({
+            "text": "Set a timer",
+            "language": "en",
+            "agent_id": "conversation.assistant"
+        })))
+        .with_body(
+            r#"{
+                "response": {
+                    "speech": {"plain": {"speech": "For how long?", "extra_data": null}},
+                    "card": {},
+                    "language": "en",
+                    "response_type": "query_answer",
+                    "data": {"targets": [], "success": [], "failed": []}
+                },
+                "conversation_id": "01HQ2ZQ8X0W7M1",
+                "continue_conversation": true
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let second_turn = create_mock_server(&mut server, "/api/conversation/process")
+        .match_body(mockito::Matcher::Json(json!({
+            "text": "Five minutes",
+            "language": "en",
+            "agent_id": "conversation.assistant",
+            "conversation_id": "01HQ2ZQ8X0W7M1"
+        })))
+        .with_body(
+            r#"{
+                "response": {
+                    "speech": {"plain": {"speech": "Timer set for 5 minutes", "extra_data": null}},
+                    "card": {},
+                    "language": "en",
+                    "response_type": "action_done",
+                    "data": {"targets": [], "success": [], "failed": []}
+                },
+                "conversation_id": "01HQ2ZQ8X0W7M1",
+                "continue_conversation": false
+            }"#,
+        )
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let mut session = ConversationSession::new(client)
+        .with_language("en")
+        .with_agent_id("conversation.assistant");
+
+    assert_eq!(session.conversation_id(), None);
+
+    let response = session.process("Set a timer").await?;
+    assert!(response.continue_conversation);
+    assert_eq!(
+        response.response.response_type,
+        post::IntentResponseType::QueryAnswer
+    );
+    assert_eq!(session.conversation_id(), Some("01HQ2ZQ8X0W7M1"));
+
+    let response = session.process("Five minutes").await?;
+    assert!(!response.continue_conversation);
+    assert_eq!(
+        response
+            .response
+            .speech
+            .plain
+            .as_ref()
+            .map(|s| s.speech.as_str()),
+        Some("Timer set for 5 minutes")
+    );
+
+    session.reset();
+    assert_eq!(session.conversation_id(), None);
+
+    first_turn.assert_async().await;
+    second_turn.assert_async().await;
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use home_assistant_rest::{errors, Client};
+use mockito::{Matcher, Mock, ServerGuard};
+use serde::{Deserialize, Serialize};
+
+fn create_mock_server(server: &mut ServerGuard, method: &str, endpoint: &str) -> Mock {
+    server
+        .mock(method, endpoint)
+        .match_header("content-type", "application/json")
+        .match_header("Authorization", "Bearer test_token")
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ShoppingListItem {
+    name: String,
+    complete: bool,
+}
+
+#[tokio::test]
+async fn test_raw_get_json_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "GET", "/api/shopping_list")
+        .with_body(r#"[{"name": "Milk", "complete": false}]"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let items: Vec<ShoppingListItem> = client.raw_get("/api/shopping_list").json().await?;
+
+    assert_eq!(
+        items,
+        vec![ShoppingListItem {
+            name: "Milk".to_owned(),
+            complete: false
+        }]
+    );
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_post_body_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "POST", "/api/shopping_list/item")
+        .match_body(r#"{"complete":false,"name":"Eggs"}"#)
+        .with_body(r#"{"name": "Eggs", "complete": false}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let item: ShoppingListItem = client
+        .raw_post("/api/shopping_list/item")
+        .body(&ShoppingListItem {
+            name: "Eggs".to_owned(),
+            complete: false,
+        })
+        .json()
+        .await?;
+
+    assert_eq!(item.name, "Eggs");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_delete_text_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "DELETE", "/api/custom/thing")
+        .with_body("Deleted")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let text = client.raw_delete("/api/custom/thing").text().await?;
+
+    assert_eq!(text, "Deleted");
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_get_bytes_with_query_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "GET", "/api/custom/image")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("size".to_owned(), "large".to_owned()),
+            Matcher::UrlEncoded("time".to_owned(), "2024-01-01T00:00:00+01:00".to_owned()),
+        ]))
+        .with_body([0xde, 0xad, 0xbe, 0xef])
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let bytes = client
+        .raw_get("/api/custom/image")
+        .query("size", "large")
+        .query("time", "2024-01-01T00:00:00+01:00")
+        .bytes()
+        .await?;
+
+    assert_eq!(bytes.as_ref(), [0xde, 0xad, 0xbe, 0xef]);
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_error_response_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "GET", "/api/shopping_list")
+        .with_status(404)
+        .with_body(r#"{"message": "Not found."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let result = client.raw_get("/api/shopping_list").text().await;
+
+    match result {
+        Err(errors::Error::ApiErrorResponse { status, message }) => {
+            assert_eq!(status, 404);
+            assert_eq!(message, "Not found.");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_raw_plaintext_error_response_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let mock_server = create_mock_server(&mut server, "GET", "/api/shopping_list")
+        .with_status(401)
+        .with_body("401: Unauthorized")
+        .create_async()
+        .await;
+
+    let client = Client::new(server.url().as_str(), "test_token")?;
+    let result = client.raw_get("/api/shopping_list").text().await;
+
+    assert!(matches!(
+        result,
+        Err(errors::Error::ApiErrorResponse { status, ref message })
+            if status == 401 && message == "401: Unauthorized"
+    ));
+
+    mock_server.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_base_path_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = mockito::Server::new_async().await;
+
+    let raw_mock = create_mock_server(&mut server, "GET", "/homeassistant/api/shopping_list")
+        .with_body("[]")
+        .create_async()
+        .await;
+    let api_status_mock = create_mock_server(&mut server, "GET", "/homeassistant/api/")
+        .with_body(r#"{"message": "API running."}"#)
+        .create_async()
+        .await;
+
+    let client = Client::new(
+        format!("{}/homeassistant/", server.url()).as_str(),
+        "test_token",
+    )?;
+
+    let items: Vec<ShoppingListItem> = client.raw_get("api/shopping_list").json().await?;
+    assert!(items.is_empty());
+
+    let api_status = client.get_api_status().await?;
+    assert_eq!(api_status.message, "API running.");
+
+    raw_mock.assert_async().await;
+    api_status_mock.assert_async().await;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use home_assistant_rest::{errors, Client, Registry};
+use mockito::{Mock, ServerGuard};
+
+async fn create_api_status_mock(server: &mut ServerGuard, body: &str) -> Mock {
+    server
+        .mock("GET", "/api/")
+        .match_header("Authorization", "Bearer test_token")
+        .with_body(body)
+        .create_async()
+        .await
+}
+
+#[tokio::test]
+async fn test_run_all_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut office = mockito::Server::new_async().await;
+    let mut warehouse = mockito::Server::new_async().await;
+
+    let office_mock = create_api_status_mock(&mut office, r#"{"message": "API running."}"#).await;
+    let warehouse_mock = create_api_status_mock(&mut warehouse, "not json").await;
+
+    let mut registry = Registry::new();
+    registry.insert("office", Client::new(office.url().as_str(), "test_token")?);
+    registry.insert(
+        "warehouse",
+        Client::new(warehouse.url().as_str(), "test_token")?,
+    );
+
+    let results = registry
+        .run_all(|client| async move { client.get_api_status().await })
+        .await;
+
+    assert_eq!(results.len(), 2);
+    assert!(!results.all_succeeded());
+
+    let successes = results.successes().collect::<Vec<_>>();
+    assert_eq!(successes.len(), 1);
+    assert_eq!(successes[0].0, "office");
+    assert_eq!(successes[0].1.message, "API running.");
+
+    let failures = results.failures().collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, "warehouse");
+    assert!(matches!(failures[0].1, errors::Error::RequestFailed(_)));
+
+    let error = results.into_result().unwrap_err();
+    assert_eq!(error.failures.len(), 1);
+    assert!(error
+        .to_string()
+        .starts_with("1 instance(s) failed; warehouse: "));
+
+    office_mock.assert_async().await;
+    warehouse_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_selected_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut office = mockito::Server::new_async().await;
+    let mut warehouse = mockito::Server::new_async().await;
+
+    let office_mock = create_api_status_mock(&mut office, r#"{"message": "API running."}"#)
+        .await
+        .expect(1);
+    let warehouse_mock = warehouse
+        .mock("GET", "/api/")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let mut registry = Registry::new();
+    registry.insert("office", Client::new(office.url().as_str(), "test_token")?);
+    registry.insert(
+        "warehouse",
+        Client::new(warehouse.url().as_str(), "test_token")?,
+    );
+
+    let results = registry
+        .run_selected(["office", "garage", "office"], |client| async move {
+            client.get_api_status().await
+        })
+        .await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results.get("office").unwrap().is_ok());
+    assert!(matches!(
+        results.get("garage"),
+        Some(Err(errors::Error::UnknownInstance(name))) if name == "garage"
+    ));
+    assert!(results.get("warehouse").is_none());
+
+    office_mock.assert_async().await;
+    warehouse_mock.assert_async().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_into_result_all_succeeded_async() -> Result<(), Box<dyn std::error::Error>> {
+    let mut office = mockito::Server::new_async().await;
+    let office_mock = create_api_status_mock(&mut office, r#"{"message": "API running."}"#).await;
+
+    let mut registry = Registry::new();
+    registry.insert("office", Client::new(office.url().as_str(), "test_token")?);
+
+    let statuses = registry
+        .run_all(|client| async move { client.get_api_status().await })
+        .await
+        .into_result()?;
+
+    assert_eq!(statuses["office"].message, "API running.");
+
+    office_mock.assert_async().await;
+
+    Ok(())
+}